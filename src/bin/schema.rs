@@ -1,4 +1,4 @@
-use cosmwasm_bidding::msg::{ExecMsg, InstantiateMsg, QueryMsg};
+use cosmwasm_bidding::msg::{ExecMsg, InstantiateMsg, MigrateMsg, QueryMsg};
 use cosmwasm_schema::write_api;
 
 fn main() {
@@ -6,5 +6,6 @@ fn main() {
         instantiate: InstantiateMsg,
         execute: ExecMsg,
         query: QueryMsg,
+        migrate: MigrateMsg,
     }
 }
@@ -1,14 +1,26 @@
-use crate::msg::InstantiateMsg;
-use crate::state::{State, STATE};
-use cosmwasm_std::{DepsMut, MessageInfo, Response, StdResult, Uint128};
-use cw2::set_contract_version;
+use crate::error::ContractError;
+use crate::msg::{AssetInfo, CommissionPayout, InstantiateMsg, MigrateMsg};
+use crate::state::{
+    bids, Asset, Commission, CommissionRecipient, State, COMMISSION_EARNED, LEGACY_BIDS,
+    LEGACY_STATE, REFERRALS, STATE, STATE_V2, STATE_V3,
+};
+use cosmwasm_std::{
+    Addr, Decimal, DepsMut, Env, MessageInfo, Order, Response, StdError, StdResult, Storage,
+    Uint128,
+};
+use cw2::{get_contract_version, set_contract_version};
+use semver::Version;
 
 const CONTRACT_NAME: &str = env!("CARGO_PKG_NAME");
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
-const DENOM: &str = "ATOM";
-const COMMISSION_DEFAULT: u8 = 5;
+const COMMISSION_DEFAULT: u64 = 5;
 
-pub fn instantiate(deps: DepsMut, info: MessageInfo, msg: InstantiateMsg) -> StdResult<Response> {
+pub fn instantiate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
     let mut owner = info.sender;
@@ -16,92 +28,622 @@ pub fn instantiate(deps: DepsMut, info: MessageInfo, msg: InstantiateMsg) -> Std
         owner = deps.api.addr_validate(&owner_addr)?
     }
 
-    let commission_percent = msg.commission_percent.unwrap_or(COMMISSION_DEFAULT);
+    let guardian = msg
+        .guardian
+        .map(|guardian| deps.api.addr_validate(&guardian))
+        .transpose()?;
+
+    let beneficiary = match msg.beneficiary {
+        Some(beneficiary) => deps.api.addr_validate(&beneficiary)?,
+        None => owner.clone(),
+    };
+
+    let commission_recipients = match msg.commission_recipients {
+        Some(shares) => shares
+            .into_iter()
+            .map(|share| {
+                Ok(CommissionRecipient {
+                    address: deps.api.addr_validate(&share.address)?,
+                    weight: share.weight,
+                })
+            })
+            .collect::<StdResult<Vec<_>>>()?,
+        None => vec![CommissionRecipient {
+            address: owner.clone(),
+            weight: Decimal::one(),
+        }],
+    };
+
+    let weights_valid = commission_recipients
+        .iter()
+        .all(|recipient| !recipient.weight.is_zero())
+        && commission_recipients
+            .iter()
+            .map(|recipient| recipient.weight)
+            .sum::<Decimal>()
+            == Decimal::one();
+    let addresses_unique = commission_recipients
+        .iter()
+        .enumerate()
+        .all(|(i, recipient)| {
+            commission_recipients[..i]
+                .iter()
+                .all(|other| other.address != recipient.address)
+        });
+    if !weights_valid || !addresses_unique {
+        return Err(ContractError::InvalidCommissionRecipients {});
+    }
+
+    let asset = match msg.asset {
+        AssetInfo::Native { denom } => {
+            if denom.is_empty() {
+                return Err(StdError::generic_err("Denom must not be empty").into());
+            }
+            Asset::Native(denom)
+        }
+        AssetInfo::Cw20 { address } => Asset::Cw20(deps.api.addr_validate(&address)?),
+    };
+
+    if let Some(end_time) = msg.end_time {
+        if end_time <= msg.start_time.unwrap_or(env.block.time) {
+            return Err(ContractError::InvalidTimeRange {});
+        }
+    }
+
+    if let Some(ref anti_sniping) = msg.anti_sniping {
+        if msg.end_time.is_none() || anti_sniping.window == 0 || anti_sniping.extension == 0 {
+            return Err(ContractError::InvalidAntiSniping {});
+        }
+    }
+
+    if let Some(buy_now_price) = msg.buy_now_price {
+        if buy_now_price.is_zero()
+            || buy_now_price < msg.reserve_price.unwrap_or_default()
+            || buy_now_price < msg.min_opening_bid.unwrap_or_default()
+        {
+            return Err(ContractError::InvalidBuyNowPrice {});
+        }
+    }
+
+    if msg
+        .min_increment_rate
+        .is_some_and(|rate| rate > Decimal::one())
+    {
+        return Err(ContractError::InvalidMinIncrementRate {});
+    }
+
+    let referral_share = msg.referral_share.unwrap_or_default();
+    if referral_share > Decimal::one() {
+        return Err(ContractError::InvalidReferralShare {});
+    }
+
+    let commission = msg
+        .commission
+        .unwrap_or_else(|| Commission::flat(Decimal::percent(COMMISSION_DEFAULT)));
+    if !commission.is_valid() {
+        return Err(ContractError::InvalidCommission {});
+    }
+
     STATE.save(
         deps.storage,
         &State {
             closed: false,
-            owner,
+            owner: Some(owner),
+            guardian,
+            paused: false,
+            beneficiary,
+            commission_recipients,
+            asset,
             commission_total: Uint128::new(0),
-            commission_percent,
+            commission,
+            commission_dust: Decimal::zero(),
+            referral_share,
+            commission_payout: msg.commission_payout.unwrap_or(CommissionPayout::Immediate),
             max_bid: None,
+            total_retractable: Uint128::zero(),
+            start_time: msg.start_time,
+            end_time: msg.end_time,
+            anti_sniping: msg.anti_sniping,
+            reserve_price: msg.reserve_price,
+            min_opening_bid: msg.min_opening_bid,
+            min_increment: msg.min_increment,
+            min_increment_rate: msg.min_increment_rate,
+            buy_now_price: msg.buy_now_price,
+            bought_out: false,
+            cancelled: false,
+            commissions_refunded: false,
         },
     )?;
 
     Ok(Response::new())
 }
 
+pub fn migrate(deps: DepsMut, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::InvalidContract {
+            contract: stored.contract,
+        });
+    }
+
+    let parse = |version: &str| {
+        Version::parse(version).map_err(|err| StdError::generic_err(err.to_string()))
+    };
+    let stored_version = parse(&stored.version)?;
+    let current_version = parse(CONTRACT_VERSION)?;
+    if stored_version > current_version {
+        return Err(ContractError::Downgrade {
+            stored: stored.version,
+            current: CONTRACT_VERSION.to_owned(),
+        });
+    }
+
+    // 0.1.0 kept a hard-coded ATOM denom and a whole percent commission
+    if stored_version < Version::new(0, 2, 0) {
+        migrate_legacy_state(deps.storage)?;
+    } else if stored_version < Version::new(0, 3, 0) {
+        migrate_v2_state(deps.storage)?;
+    } else if stored_version < Version::new(0, 4, 0) {
+        migrate_v3_state(deps.storage)?;
+    }
+
+    // 0.3.0 indexes bids by their total
+    if stored_version < Version::new(0, 3, 0) {
+        index_bids(deps.storage)?;
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", stored.version)
+        .add_attribute("to_version", CONTRACT_VERSION))
+}
+
+fn total_retractable(storage: &dyn Storage) -> StdResult<Uint128> {
+    LEGACY_BIDS
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, bid)| bid.retractable))
+        .sum()
+}
+
+fn migrate_legacy_state(storage: &mut dyn Storage) -> StdResult<()> {
+    let legacy = LEGACY_STATE.load(storage)?;
+    let total_retractable = total_retractable(storage)?;
+
+    // the legacy commission pot was never split, it all belongs to the owner
+    if !legacy.commission_total.is_zero() {
+        COMMISSION_EARNED.save(storage, &legacy.owner, &legacy.commission_total)?;
+    }
+
+    STATE.save(
+        storage,
+        &State {
+            closed: legacy.closed,
+            owner: Some(legacy.owner.clone()),
+            guardian: None,
+            paused: false,
+            beneficiary: legacy.owner.clone(),
+            commission_recipients: vec![CommissionRecipient {
+                address: legacy.owner,
+                weight: Decimal::one(),
+            }],
+            asset: Asset::Native("ATOM".to_owned()),
+            commission_total: legacy.commission_total,
+            commission: Commission::flat(Decimal::percent(legacy.commission_percent.into())),
+            commission_dust: Decimal::zero(),
+            referral_share: Decimal::zero(),
+            commission_payout: CommissionPayout::Deferred,
+            max_bid: legacy.max_bid,
+            total_retractable,
+            start_time: None,
+            end_time: None,
+            anti_sniping: None,
+            reserve_price: None,
+            min_opening_bid: None,
+            min_increment: None,
+            min_increment_rate: None,
+            buy_now_price: None,
+            bought_out: false,
+            cancelled: false,
+            commissions_refunded: false,
+        },
+    )
+}
+
+fn migrate_v2_state(storage: &mut dyn Storage) -> StdResult<()> {
+    let state = STATE_V2.load(storage)?;
+    let total_retractable = total_retractable(storage)?;
+
+    STATE.save(
+        storage,
+        &State {
+            closed: state.closed,
+            owner: Some(state.owner),
+            guardian: None,
+            paused: false,
+            beneficiary: state.beneficiary,
+            commission_recipients: state.commission_recipients,
+            asset: state.asset,
+            commission_total: state.commission_total,
+            commission: state.commission,
+            commission_dust: state.commission_dust,
+            referral_share: state.referral_share,
+            commission_payout: state.commission_payout,
+            max_bid: state.max_bid,
+            total_retractable,
+            start_time: state.start_time,
+            end_time: state.end_time,
+            anti_sniping: state.anti_sniping,
+            reserve_price: state.reserve_price,
+            min_opening_bid: state.min_opening_bid,
+            min_increment: state.min_increment,
+            min_increment_rate: state.min_increment_rate,
+            buy_now_price: state.buy_now_price,
+            bought_out: state.bought_out,
+            cancelled: false,
+            commissions_refunded: false,
+        },
+    )
+}
+
+fn migrate_v3_state(storage: &mut dyn Storage) -> StdResult<()> {
+    let state = STATE_V3.load(storage)?;
+    let total_retractable = total_retractable(storage)?;
+
+    STATE.save(
+        storage,
+        &State {
+            closed: state.closed,
+            owner: state.owner,
+            guardian: state.guardian,
+            paused: state.paused,
+            beneficiary: state.beneficiary,
+            commission_recipients: state.commission_recipients,
+            asset: state.asset,
+            commission_total: state.commission_total,
+            commission: state.commission,
+            commission_dust: state.commission_dust,
+            referral_share: state.referral_share,
+            commission_payout: state.commission_payout,
+            max_bid: state.max_bid,
+            total_retractable,
+            start_time: state.start_time,
+            end_time: state.end_time,
+            anti_sniping: state.anti_sniping,
+            reserve_price: state.reserve_price,
+            min_opening_bid: state.min_opening_bid,
+            min_increment: state.min_increment,
+            min_increment_rate: state.min_increment_rate,
+            buy_now_price: state.buy_now_price,
+            bought_out: state.bought_out,
+            cancelled: state.cancelled,
+            commissions_refunded: state.commissions_refunded,
+        },
+    )
+}
+
+fn index_bids(storage: &mut dyn Storage) -> StdResult<()> {
+    let entries = LEGACY_BIDS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    for (address, bid) in entries {
+        bids().save(storage, &address, &bid)?;
+    }
+
+    Ok(())
+}
+
+// part of an incoming bid going over the buy-now price, which is refunded instead of bid
+fn buy_now_excess(
+    storage: &dyn Storage,
+    state: &State,
+    sender: &Addr,
+    incoming_bid: Uint128,
+) -> StdResult<Uint128> {
+    let price = match state.buy_now_price {
+        Some(price) => price,
+        None => return Ok(Uint128::zero()),
+    };
+
+    let current_bid = bids().may_load(storage, sender)?.unwrap_or_default();
+    Ok((current_bid.total() + incoming_bid)
+        .saturating_sub(price)
+        .min(incoming_bid))
+}
+
+struct ValidBid {
+    total: Uint128,
+    commission: Uint128,
+    commission_dust: Decimal,
+    retractable: Uint128,
+}
+
+// checks whether the sender is able to bid at all, regardless of the amount
+fn check_can_bid(env: &Env, state: &State, sender: &Addr) -> Result<(), ContractError> {
+    if state.is_owner(sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if state.closed {
+        return Err(ContractError::BiddingClosed {});
+    }
+
+    if state.paused {
+        return Err(ContractError::Paused {});
+    }
+
+    if let Some(start_time) = state.start_time {
+        if env.block.time < start_time {
+            return Err(ContractError::BiddingNotStarted {});
+        }
+    }
+
+    if let Some(end_time) = state.end_time {
+        if env.block.time >= end_time {
+            return Err(ContractError::BiddingEnded {});
+        }
+    }
+
+    Ok(())
+}
+
+// checks an incoming bid against the bidding rules, shared by bidding and its simulation
+fn validate_bid(
+    storage: &dyn Storage,
+    env: &Env,
+    state: &State,
+    sender: &Addr,
+    incoming_bid: Uint128,
+) -> Result<ValidBid, ContractError> {
+    check_can_bid(env, state, sender)?;
+
+    let current_bid = bids().may_load(storage, sender)?.unwrap_or_default();
+    let total = incoming_bid + current_bid.total();
+
+    if let Some(ref max_bid) = state.max_bid {
+        if total <= max_bid.1 {
+            return Err(ContractError::BidTooSmall {});
+        }
+    }
+
+    // reaching the buy-now price always wins, whatever the increment rules would require
+    let buys_out = state.buy_now_price.is_some_and(|price| total >= price);
+    let min_bid = state.min_bid()?;
+    if !buys_out && total < min_bid {
+        return Err(ContractError::BidBelowMinimum { min: min_bid });
+    }
+
+    // calculate commission and retractable right away
+    let (commission, dust) = state.commission.calculate(incoming_bid)?;
+    if commission > incoming_bid {
+        return Err(ContractError::BidBelowCommission { commission });
+    }
+
+    Ok(ValidBid {
+        total,
+        commission,
+        commission_dust: state.commission_dust + dust,
+        retractable: incoming_bid - commission,
+    })
+}
+
+// commissions which did not leave the contract yet
+fn commission_held(storage: &dyn Storage, state: &State) -> StdResult<Uint128> {
+    if state.commissions_refunded {
+        return bids()
+            .range(storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(_, bid)| bid.commission))
+            .sum();
+    }
+
+    let mut held = REFERRALS
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, referral)| referral.earned - referral.claimed))
+        .sum::<StdResult<Uint128>>()?;
+
+    if state.commission_payout == CommissionPayout::Deferred && !state.closed {
+        for recipient in &state.commission_recipients {
+            held += COMMISSION_EARNED
+                .may_load(storage, &recipient.address)?
+                .unwrap_or_default();
+        }
+    }
+
+    Ok(held)
+}
+
 pub mod exec {
-    use crate::contract::DENOM;
+    use super::{buy_now_excess, commission_held, validate_bid, ValidBid};
     use crate::error::ContractError;
     use crate::error::ContractError::BidMissing;
+    use crate::msg::{CommissionPayout, ReceiveMsg};
     use cosmwasm_std::{
-        coins, BankMsg, DepsMut, MessageInfo, Response, StdError, StdResult, Uint128,
+        coins, from_binary, Addr, BankMsg, Binary, CosmosMsg, DepsMut, Env, Event, MessageInfo,
+        Order, Response, StdResult, Storage, Timestamp, Uint128,
     };
+    use cw20::Cw20ReceiveMsg;
+    use cw_utils::{must_pay, nonpayable};
 
-    use crate::state::{Bid, BIDS, STATE};
+    use crate::state::{
+        bid_events, bids, Asset, Bid, BidEvent, PendingOwner, State, BID_SEQUENCE,
+        COMMISSION_EARNED, PENDING_OWNER, REFERRALS, STATE,
+    };
 
-    pub fn bid(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
-        let mut state = STATE.load(deps.storage)?;
-        if info.sender.clone() == state.owner {
-            return Err(ContractError::Unauthorized {});
-        }
+    pub fn bid(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        referrer: Option<String>,
+    ) -> Result<Response, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        let denom = match state.asset {
+            Asset::Native(ref denom) => denom,
+            Asset::Cw20(_) => return Err(ContractError::InvalidAsset {}),
+        };
 
-        if state.closed {
-            return Err(ContractError::BiddingClosed {});
-        }
+        let incoming_bid = must_pay(&info, denom)?;
 
-        let incoming_bid = info
-            .funds
-            .iter()
-            .find(|x| x.denom == DENOM)
-            .ok_or_else(|| StdError::generic_err(format!("No {} tokens sent", &DENOM)))?
-            .amount;
-        let current_bid = BIDS
-            .may_load(deps.storage, &info.sender)?
-            .unwrap_or_default();
-        let total_bid = incoming_bid + current_bid.retractable + current_bid.commission;
+        place_bid(deps, env, state, info.sender, incoming_bid, referrer)
+    }
+
+    pub fn receive(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        msg: Cw20ReceiveMsg,
+    ) -> Result<Response, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        // only the token the auction is priced in can call the hook
+        match state.asset {
+            Asset::Cw20(ref address) if *address == info.sender => {}
+            _ => return Err(ContractError::InvalidAsset {}),
+        }
 
-        if let Some(max_bid) = state.max_bid {
-            if total_bid <= max_bid.1 {
-                return Err(ContractError::BidTooSmall {});
+        let sender = deps.api.addr_validate(&msg.sender)?;
+        match from_binary(&msg.msg)? {
+            ReceiveMsg::Bid { referrer } => {
+                place_bid(deps, env, state, sender, msg.amount, referrer)
             }
+            ReceiveMsg::Cancel {} => cancel_bidding(deps, state, sender, true, msg.amount),
+        }
+    }
+
+    fn place_bid(
+        deps: DepsMut,
+        env: Env,
+        mut state: State,
+        sender: Addr,
+        mut incoming_bid: Uint128,
+        referrer: Option<String>,
+    ) -> Result<Response, ContractError> {
+        let referrer = referrer
+            .map(|referrer| deps.api.addr_validate(&referrer))
+            .transpose()?;
+        if referrer.as_ref() == Some(&sender) {
+            return Err(ContractError::InvalidReferrer {});
         }
 
+        let excess = buy_now_excess(deps.storage, &state, &sender, incoming_bid)?;
+        incoming_bid -= excess;
+
+        let ValidBid {
+            total: total_bid,
+            commission,
+            commission_dust,
+            retractable,
+        } = validate_bid(deps.storage, &env, &state, &sender, incoming_bid)?;
+
         // save new max bid for future comparisons
-        state.max_bid = Some((info.sender.clone(), total_bid));
+        state.max_bid = Some((sender.clone(), total_bid));
+
+        state.commission_dust = commission_dust;
+        bids().update(deps.storage, &sender, |bid: Option<Bid>| -> StdResult<_> {
+            let bid = bid.unwrap_or_default();
+            Ok(Bid {
+                commission: bid.commission + commission,
+                retractable: bid.retractable + retractable,
+            })
+        })?;
+
+        state.commission_total += commission;
+        state.total_retractable += retractable;
 
-        // calculate commission and retractable right away
-        let commission = incoming_bid * Uint128::from(state.commission_percent) / Uint128::new(100);
-        let retractable = incoming_bid - commission;
-        BIDS.update(
+        let sequence = BID_SEQUENCE.may_load(deps.storage)?.unwrap_or_default() + 1;
+        BID_SEQUENCE.save(deps.storage, &sequence)?;
+        bid_events().save(
             deps.storage,
-            &info.sender,
-            |bid: Option<Bid>| -> StdResult<_> {
-                let bid = bid.unwrap_or_default();
-                Ok(Bid {
-                    commission: bid.commission + commission,
-                    retractable: bid.retractable + retractable,
-                })
+            sequence,
+            &BidEvent {
+                bidder: sender.clone(),
+                height: env.block.height,
+                time: env.block.time,
+                amount: incoming_bid,
+                commission,
+                total: total_bid,
             },
         )?;
 
-        // future winning pot
-        state.commission_total += commission;
+        let mut response = Response::default()
+            .add_attribute("action", "bid")
+            .add_attribute("sender", sender.as_str())
+            .add_attribute("total_bid", total_bid);
+
+        // the referrer's share is credited and claimed later, the rest is split between recipients
+        let mut shared_commission = commission;
+        if let Some(ref referrer) = referrer {
+            let reward = commission * state.referral_share;
+            if !reward.is_zero() {
+                REFERRALS.update(deps.storage, referrer, |referral| -> StdResult<_> {
+                    let mut referral = referral.unwrap_or_default();
+                    referral.earned += reward;
+                    Ok(referral)
+                })?;
+                shared_commission -= reward;
+                response = response
+                    .add_attribute("referrer", referrer.as_str())
+                    .add_attribute("referral_reward", reward);
+            }
+        }
+
+        // deferred commissions are paid out to the recipients on close
+        for (recipient, amount) in state.split_commission(shared_commission) {
+            if amount.is_zero() {
+                continue;
+            }
+
+            COMMISSION_EARNED.update(deps.storage, &recipient, |earned| -> StdResult<_> {
+                Ok(earned.unwrap_or_default() + amount)
+            })?;
+
+            if state.commission_payout == CommissionPayout::Immediate {
+                response = response.add_message(state.asset.transfer_msg(&recipient, amount)?);
+            }
+        }
+
+        // reaching the buy-now price wins the bidding outright
+        if state.buy_now_price.is_some_and(|price| total_bid >= price) {
+            state.bought_out = true;
+            let messages = settle(deps.storage, &mut state)?;
+            STATE.save(deps.storage, &state)?;
+
+            if !excess.is_zero() {
+                response = response
+                    .add_attribute("refunded", excess)
+                    .add_message(state.asset.transfer_msg(&sender, excess)?);
+            }
+
+            return Ok(response
+                .add_attribute("bought_out", "true")
+                .add_messages(messages));
+        }
+
+        // push the end time out for bids landing right before it
+        if let (Some(end_time), Some(anti_sniping)) = (state.end_time, &state.anti_sniping) {
+            if env.block.time.plus_seconds(anti_sniping.window) >= end_time {
+                let end_time = end_time.plus_seconds(anti_sniping.extension);
+                state.end_time = Some(end_time);
+                response = response.add_event(
+                    Event::new("auction_extended")
+                        .add_attribute("sender", sender.as_str())
+                        .add_attribute("end_time", end_time.seconds().to_string()),
+                );
+            }
+        }
+
         STATE.save(deps.storage, &state)?;
 
-        Ok(Response::default()
-            .add_attribute("action", "bid")
-            .add_attribute("sender", info.sender.as_str())
-            .add_attribute("total_bid", total_bid))
+        Ok(response)
     }
 
     pub fn retract(
         deps: DepsMut,
         info: MessageInfo,
         address: Option<String>,
+        msg: Option<Binary>,
     ) -> Result<Response, ContractError> {
-        let state = STATE.load(deps.storage)?;
+        let mut state = STATE.load(deps.storage)?;
 
         if !state.closed {
             return Err(ContractError::BiddingNotClosed {});
@@ -109,32 +651,288 @@ pub mod exec {
 
         let mut messages = vec![];
 
-        let current_bid = BIDS.may_load(deps.storage, &info.sender)?;
+        let current_bid = bids().may_load(deps.storage, &info.sender)?;
         match current_bid {
             Some(bid) => {
-                let mut to_address = info.sender.clone();
+                let to_address = match address {
+                    Some(address) => deps.api.addr_validate(&address)?,
+                    None => info.sender.clone(),
+                };
 
-                if let Some(address) = address {
-                    to_address = deps.api.addr_validate(&address).unwrap_or(to_address);
+                // commissions are only returned when a cancelled bidding refunds them
+                let mut amount = bid.retractable;
+                if state.commissions_refunded {
+                    amount += bid.commission;
                 }
+                state.total_retractable -= bid.retractable;
 
-                messages.push(BankMsg::Send {
-                    to_address: to_address.to_string(),
-                    amount: coins(u128::from(bid.retractable), DENOM),
-                })
+                // with a hook the receiver contract is called along with the funds
+                let message = match msg {
+                    Some(msg) => state.asset.send_msg(&to_address, amount, msg)?,
+                    None => state.asset.transfer_msg(&to_address, amount)?,
+                };
+                messages.push(message)
             }
             None => return Err(BidMissing {}),
         }
 
-        BIDS.remove(deps.storage, &info.sender);
+        bids().remove(deps.storage, &info.sender)?;
+        STATE.save(deps.storage, &state)?;
 
         Ok(Response::new().add_messages(messages))
     }
 
-    pub fn close(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    pub fn claim_referral(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+        let state = STATE.load(deps.storage)?;
+
+        // deferred commissions, referral rewards included, are only released on close
+        if state.commission_payout == CommissionPayout::Deferred && !state.closed {
+            return Err(ContractError::BiddingNotClosed {});
+        }
+
+        let mut referral = REFERRALS
+            .may_load(deps.storage, &info.sender)?
+            .unwrap_or_default();
+        let claimable = referral.earned - referral.claimed;
+        if claimable.is_zero() {
+            return Err(ContractError::NoReferralRewards {});
+        }
+
+        referral.claimed += claimable;
+        REFERRALS.save(deps.storage, &info.sender, &referral)?;
+
+        Ok(Response::new()
+            .add_message(state.asset.transfer_msg(&info.sender, claimable)?)
+            .add_attribute("action", "claim_referral")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("amount", claimable))
+    }
+
+    pub fn cancel(
+        deps: DepsMut,
+        info: MessageInfo,
+        refund_commissions: bool,
+    ) -> Result<Response, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        // native funds only ever cover commissions refunded on top of what the contract holds
+        let funds = match state.asset {
+            Asset::Native(ref denom) if refund_commissions && !info.funds.is_empty() => {
+                must_pay(&info, denom)?
+            }
+            _ => {
+                nonpayable(&info)?;
+                Uint128::zero()
+            }
+        };
+
+        cancel_bidding(deps, state, info.sender, refund_commissions, funds)
+    }
+
+    fn cancel_bidding(
+        deps: DepsMut,
+        mut state: State,
+        sender: Addr,
+        refund_commissions: bool,
+        funds: Uint128,
+    ) -> Result<Response, ContractError> {
+        if !state.is_owner(&sender) {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        if state.closed {
+            return Err(ContractError::BiddingClosed {});
+        }
+
         let mut messages = vec![];
+        if refund_commissions {
+            // commissions paid out already have to be sent back by the owner
+            let commissions = bids()
+                .range(deps.storage, None, None, Order::Ascending)
+                .map(|item| item.map(|(_, bid)| bid.commission))
+                .sum::<StdResult<Uint128>>()?;
+            let required = commissions.saturating_sub(commission_held(deps.storage, &state)?);
+            if funds != required {
+                return Err(ContractError::InvalidRefundFunds { required });
+            }
+
+            // held commissions stay in the contract, so nobody is owed them anymore
+            for recipient in &state.commission_recipients {
+                COMMISSION_EARNED.remove(deps.storage, &recipient.address);
+            }
+            let referrers = REFERRALS
+                .keys(deps.storage, None, None, Order::Ascending)
+                .collect::<StdResult<Vec<_>>>()?;
+            for referrer in referrers {
+                REFERRALS.remove(deps.storage, &referrer);
+            }
+
+            state.commissions_refunded = true;
+        } else {
+            messages = deferred_commission_msgs(deps.storage, &state)?;
+        }
+
+        // every bidder, the highest one included, can retract now
+        state.closed = true;
+        state.cancelled = true;
+        STATE.save(deps.storage, &state)?;
+
+        Ok(Response::new()
+            .add_messages(messages)
+            .add_attribute("action", "cancel")
+            .add_attribute("sender", sender.as_str())
+            .add_attribute("refund_commissions", refund_commissions.to_string()))
+    }
+
+    pub fn propose_owner(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        new_owner: String,
+        expiry: Option<Timestamp>,
+    ) -> Result<Response, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        if !state.is_owner(&info.sender) {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        if expiry.is_some_and(|expiry| expiry <= env.block.time) {
+            return Err(ContractError::OwnershipProposalExpired {});
+        }
+
+        let new_owner = deps.api.addr_validate(&new_owner)?;
+        PENDING_OWNER.save(
+            deps.storage,
+            &PendingOwner {
+                address: new_owner.clone(),
+                expiry,
+            },
+        )?;
+
+        Ok(Response::new()
+            .add_attribute("action", "propose_owner")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("new_owner", new_owner.as_str()))
+    }
+
+    pub fn accept_ownership(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+    ) -> Result<Response, ContractError> {
+        let pending = PENDING_OWNER
+            .may_load(deps.storage)?
+            .ok_or(ContractError::NoPendingOwner {})?;
+        if pending.address != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        if pending
+            .expiry
+            .is_some_and(|expiry| expiry <= env.block.time)
+        {
+            return Err(ContractError::OwnershipProposalExpired {});
+        }
+
+        let mut state = STATE.load(deps.storage)?;
+        state.owner = Some(pending.address);
+        STATE.save(deps.storage, &state)?;
+        PENDING_OWNER.remove(deps.storage);
+
+        Ok(Response::new()
+            .add_attribute("action", "accept_ownership")
+            .add_attribute("sender", info.sender.as_str()))
+    }
+
+    pub fn renounce_ownership(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+        let mut state = STATE.load(deps.storage)?;
+        if !state.is_owner(&info.sender) {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        // nobody could close a bidding without an end time anymore, locking the bids forever
+        if !state.closed && state.end_time.is_none() {
+            return Err(ContractError::OpenEndedBidding {});
+        }
+
+        state.owner = None;
+        STATE.save(deps.storage, &state)?;
+        PENDING_OWNER.remove(deps.storage);
+
+        Ok(Response::new()
+            .add_attribute("action", "renounce_ownership")
+            .add_attribute("sender", info.sender.as_str()))
+    }
+
+    pub fn set_paused(
+        deps: DepsMut,
+        info: MessageInfo,
+        paused: bool,
+    ) -> Result<Response, ContractError> {
         let mut state = STATE.load(deps.storage)?;
-        if info.sender.clone() != state.owner {
+        if !state.is_owner(&info.sender) && !state.is_guardian(&info.sender) {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        state.paused = paused;
+        STATE.save(deps.storage, &state)?;
+
+        let action = if paused { "pause" } else { "unpause" };
+        Ok(Response::new()
+            .add_attribute("action", action)
+            .add_attribute("sender", info.sender.as_str()))
+    }
+
+    // sends the owner whatever the contract holds beyond what it owes
+    pub fn sweep(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        denom: String,
+    ) -> Result<Response, ContractError> {
+        let state = STATE.load(deps.storage)?;
+        if !state.is_owner(&info.sender) {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        if !state.closed {
+            return Err(ContractError::BiddingNotClosed {});
+        }
+
+        let balance = deps
+            .querier
+            .query_balance(&env.contract.address, &denom)?
+            .amount;
+        let liabilities = match state.asset {
+            Asset::Native(ref asset_denom) if *asset_denom == denom => {
+                state.total_retractable + commission_held(deps.storage, &state)?
+            }
+            _ => Uint128::zero(),
+        };
+
+        let amount = balance.saturating_sub(liabilities);
+        if amount.is_zero() {
+            return Err(ContractError::NothingToSweep { denom });
+        }
+
+        Ok(Response::new()
+            .add_message(BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: coins(amount.u128(), &denom),
+            })
+            .add_attribute("action", "sweep")
+            .add_attribute("sender", info.sender.as_str())
+            .add_attribute("denom", denom)
+            .add_attribute("amount", amount))
+    }
+
+    pub fn close(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+        let mut state = STATE.load(deps.storage)?;
+
+        // once the end time has passed anyone can close, so funds are never held hostage
+        let ended = state
+            .end_time
+            .is_some_and(|end_time| env.block.time >= end_time);
+        if !state.is_owner(&info.sender) && !ended {
             return Err(ContractError::Unauthorized {});
         }
 
@@ -142,44 +940,242 @@ pub mod exec {
             return Err(ContractError::BiddingClosed {});
         }
 
-        // if bidding commenced, send funds to the winner
+        let messages = settle(deps.storage, &mut state)?;
+        STATE.save(deps.storage, &state)?;
+
+        Ok(Response::new().add_messages(messages))
+    }
+
+    // closes the bidding, paying out the winner if there is one
+    fn settle(
+        storage: &mut dyn Storage,
+        state: &mut State,
+    ) -> Result<Vec<CosmosMsg>, ContractError> {
+        let mut messages = vec![];
+
+        // if bidding commenced and the reserve is met, the winning bid goes to the beneficiary
         if let Some(ref max_bid) = state.max_bid {
-            let winner_addr = max_bid.clone().0;
-            let winner_bid = BIDS.may_load(deps.storage, &winner_addr).unwrap();
-
-            match winner_bid {
-                Some(bid) => {
-                    let jackpot = bid.retractable + state.commission_total;
-                    BIDS.remove(deps.storage, &winner_addr);
-                    messages.push(BankMsg::Send {
-                        to_address: winner_addr.to_string(),
-                        amount: coins(u128::from(jackpot), DENOM),
-                    })
+            if state.reserve_met() {
+                let winner_addr = max_bid.clone().0;
+                let winner_bid = bids()
+                    .may_load(storage, &winner_addr)?
+                    .ok_or(BidMissing {})?;
+                bids().remove(storage, &winner_addr)?;
+                state.total_retractable -= winner_bid.retractable;
+
+                if !winner_bid.retractable.is_zero() {
+                    messages.push(
+                        state
+                            .asset
+                            .transfer_msg(&state.beneficiary, winner_bid.retractable)?,
+                    )
                 }
-                None => return Err(BidMissing {}),
             }
         }
 
+        // commissions are collected whether there is a winner or not
+        messages.extend(deferred_commission_msgs(storage, state)?);
+
         state.closed = true;
-        STATE.save(deps.storage, &state)?;
 
-        Ok(Response::new().add_messages(messages))
+        Ok(messages)
+    }
+
+    fn deferred_commission_msgs(
+        storage: &dyn Storage,
+        state: &State,
+    ) -> Result<Vec<CosmosMsg>, ContractError> {
+        let mut messages = vec![];
+
+        if state.commission_payout == CommissionPayout::Deferred {
+            for recipient in &state.commission_recipients {
+                let earned = COMMISSION_EARNED
+                    .may_load(storage, &recipient.address)?
+                    .unwrap_or_default();
+                if !earned.is_zero() {
+                    messages.push(state.asset.transfer_msg(&recipient.address, earned)?)
+                }
+            }
+        }
+
+        Ok(messages)
     }
 }
 
 pub mod query {
-    use crate::msg::{BidResp, ClosedResp, HighestResp, WinnerResp};
-    use crate::state::{BIDS, STATE};
-    use cosmwasm_std::{Deps, StdResult};
+    use super::{buy_now_excess, check_can_bid, commission_held, validate_bid};
+    use crate::msg::{
+        AccountingResp, AllBidsResp, AuctionStatus, BidEntry, BidHistoryResp, BidRecord, BidResp,
+        ClosedResp, CommissionEarned, CommissionEarnedResp, CommissionPreviewResp, ConfigResp,
+        HighestResp, MinBidToLeadResp, OwnershipResp, PausedResp, ReferralEarnings,
+        ReferralEarningsResp, SimulateBidResp, StatusResp, TopBidsResp, WinnerResp,
+    };
+    use crate::state::{
+        bid_events, bids, Bid, BidEvent, COMMISSION_EARNED, PENDING_OWNER, REFERRALS, STATE,
+    };
+    use cosmwasm_std::{Addr, Deps, Env, Order, StdError, StdResult, Uint128};
+    use cw_storage_plus::Bound;
+
+    const DEFAULT_LIMIT: u32 = 10;
+    const MAX_LIMIT: u32 = 30;
 
     pub fn bid(deps: Deps, address: String) -> StdResult<BidResp> {
         let address = deps.api.addr_validate(&address)?;
-        let bid = BIDS.may_load(deps.storage, &address)?.unwrap_or_default();
-        Ok(BidResp {
-            bid: bid.retractable + bid.commission,
+        let bid = bids().may_load(deps.storage, &address)?.unwrap_or_default();
+        Ok(BidResp { bid: bid.total() })
+    }
+
+    pub fn all_bids(
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<AllBidsResp> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start_after = start_after
+            .map(|address| deps.api.addr_validate(&address))
+            .transpose()?;
+        let start = start_after.as_ref().map(Bound::exclusive);
+
+        let bids = bids()
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| item.map(bid_entry))
+            .collect::<StdResult<_>>()?;
+
+        Ok(AllBidsResp { bids })
+    }
+
+    pub fn top_bids(deps: Deps, limit: Option<u32>) -> StdResult<TopBidsResp> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+        let bids = bids()
+            .idx
+            .total
+            .range(deps.storage, None, None, Order::Descending)
+            .take(limit)
+            .map(|item| item.map(bid_entry))
+            .collect::<StdResult<_>>()?;
+
+        Ok(TopBidsResp { bids })
+    }
+
+    pub fn bid_history(
+        deps: Deps,
+        address: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<BidHistoryResp> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let address = deps.api.addr_validate(&address)?;
+        let start = start_after.map(Bound::exclusive);
+
+        let bids = bid_events()
+            .idx
+            .bidder
+            .prefix(address)
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| item.map(bid_record))
+            .collect::<StdResult<_>>()?;
+
+        Ok(BidHistoryResp { bids })
+    }
+
+    // newest first, so `start_after` continues with older bids
+    pub fn recent_bids(
+        deps: Deps,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<BidHistoryResp> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let end = start_after.map(Bound::exclusive);
+
+        let bids = bid_events()
+            .range(deps.storage, None, end, Order::Descending)
+            .take(limit)
+            .map(|item| item.map(bid_record))
+            .collect::<StdResult<_>>()?;
+
+        Ok(BidHistoryResp { bids })
+    }
+
+    fn bid_record((sequence, event): (u64, BidEvent)) -> BidRecord {
+        BidRecord {
+            sequence,
+            bidder: event.bidder,
+            height: event.height,
+            time: event.time,
+            amount: event.amount,
+            commission: event.commission,
+            total: event.total,
+        }
+    }
+
+    fn bid_entry((address, bid): (Addr, Bid)) -> BidEntry {
+        BidEntry {
+            address,
+            retractable: bid.retractable,
+            commission: bid.commission,
+            total: bid.total(),
+        }
+    }
+
+    pub fn min_bid_to_lead(deps: Deps, env: Env, address: String) -> StdResult<MinBidToLeadResp> {
+        let state = STATE.load(deps.storage)?;
+        let address = deps.api.addr_validate(&address)?;
+        // no amount would succeed when the address cannot bid at all
+        check_can_bid(&env, &state, &address)
+            .map_err(|err| StdError::generic_err(err.to_string()))?;
+        let current_bid = bids().may_load(deps.storage, &address)?.unwrap_or_default();
+
+        // even with no rules in place a bid has to add at least a single unit
+        let mut total = state
+            .min_bid()?
+            .max(current_bid.total().checked_add(Uint128::one())?);
+        // reaching the buy-now price wins outright, so more than that is never needed
+        if let Some(price) = state.buy_now_price {
+            total = total.min(price);
+        }
+        let mut amount = total - current_bid.total();
+
+        // a fixed or minimum commission has to be covered by the bid itself
+        let (mut commission, _) = state.commission.calculate(amount)?;
+        if commission > amount {
+            amount = commission;
+            (commission, _) = state.commission.calculate(amount)?;
+        }
+
+        Ok(MinBidToLeadResp {
+            amount,
+            commission,
+            total: current_bid.total() + amount,
         })
     }
 
+    pub fn simulate_bid(
+        deps: Deps,
+        env: Env,
+        address: String,
+        amount: Uint128,
+    ) -> StdResult<SimulateBidResp> {
+        let state = STATE.load(deps.storage)?;
+        let address = deps.api.addr_validate(&address)?;
+
+        let amount = amount - buy_now_excess(deps.storage, &state, &address, amount)?;
+        let resp = match validate_bid(deps.storage, &env, &state, &address, amount) {
+            Ok(bid) => SimulateBidResp::Accepted {
+                total: bid.total,
+                commission: bid.commission,
+                retractable: bid.retractable,
+            },
+            Err(err) => SimulateBidResp::Rejected {
+                error: err.to_string(),
+            },
+        };
+
+        Ok(resp)
+    }
+
     pub fn highest(deps: Deps) -> StdResult<Option<HighestResp>> {
         let state = STATE.load(deps.storage)?;
         let max_bid = match state.max_bid {
@@ -197,7 +1193,9 @@ pub mod query {
         let state = STATE.load(deps.storage)?;
         let mut winner = None;
 
-        if state.closed {
+        let reserve_met = state.reserve_met();
+
+        if state.closed && !state.cancelled && reserve_met {
             if let Some(max_bid) = state.max_bid {
                 winner = Some(HighestResp {
                     address: max_bid.0,
@@ -206,7 +1204,10 @@ pub mod query {
             }
         }
 
-        Ok(WinnerResp { winner })
+        Ok(WinnerResp {
+            winner,
+            reserve_met,
+        })
     }
 
     pub fn closed(deps: Deps) -> StdResult<ClosedResp> {
@@ -214,6 +1215,152 @@ pub mod query {
 
         Ok(ClosedResp {
             closed: state.closed,
+            bought_out: state.bought_out,
+            cancelled: state.cancelled,
+        })
+    }
+
+    pub fn status(deps: Deps, env: Env) -> StdResult<StatusResp> {
+        let state = STATE.load(deps.storage)?;
+
+        let status = if state.cancelled {
+            AuctionStatus::Cancelled
+        } else if state.closed {
+            AuctionStatus::Settled
+        } else if state
+            .end_time
+            .is_some_and(|end_time| env.block.time >= end_time)
+        {
+            AuctionStatus::Closed
+        } else if state
+            .start_time
+            .is_some_and(|start_time| env.block.time < start_time)
+        {
+            AuctionStatus::NotStarted
+        } else {
+            AuctionStatus::Open
+        };
+
+        Ok(StatusResp { status })
+    }
+
+    pub fn accounting(deps: Deps, env: Env) -> StdResult<AccountingResp> {
+        let state = STATE.load(deps.storage)?;
+        let balance = state
+            .asset
+            .query_balance(&deps.querier, &env.contract.address)?;
+
+        let commission_held = commission_held(deps.storage, &state)?;
+
+        let liabilities = state.total_retractable + commission_held;
+
+        Ok(AccountingResp {
+            balance,
+            total_retractable: state.total_retractable,
+            commission_held,
+            liabilities,
+            solvent: balance >= liabilities,
+        })
+    }
+
+    pub fn config(deps: Deps) -> StdResult<ConfigResp> {
+        let state = STATE.load(deps.storage)?;
+
+        Ok(ConfigResp {
+            owner: state.owner,
+            guardian: state.guardian,
+            beneficiary: state.beneficiary,
+            commission_recipients: state.commission_recipients,
+            asset: state.asset.into(),
+            commission: state.commission,
+            commission_payout: state.commission_payout,
+            commission_total: state.commission_total,
+            commission_dust: state.commission_dust,
+            referral_share: state.referral_share,
+            start_time: state.start_time,
+            end_time: state.end_time,
+            anti_sniping: state.anti_sniping,
+            reserve_price: state.reserve_price,
+            min_opening_bid: state.min_opening_bid,
+            min_increment: state.min_increment,
+            min_increment_rate: state.min_increment_rate,
+            buy_now_price: state.buy_now_price,
+        })
+    }
+
+    pub fn paused(deps: Deps) -> StdResult<PausedResp> {
+        let state = STATE.load(deps.storage)?;
+
+        Ok(PausedResp {
+            paused: state.paused,
+        })
+    }
+
+    pub fn commission_preview(deps: Deps, amount: Uint128) -> StdResult<CommissionPreviewResp> {
+        let state = STATE.load(deps.storage)?;
+        let (commission, _) = state.commission.calculate(amount)?;
+
+        Ok(CommissionPreviewResp {
+            commission,
+            retractable: amount.saturating_sub(commission),
+        })
+    }
+
+    pub fn commission_earned(deps: Deps) -> StdResult<CommissionEarnedResp> {
+        let state = STATE.load(deps.storage)?;
+        let recipients = state
+            .commission_recipients
+            .into_iter()
+            .map(|recipient| {
+                let earned = COMMISSION_EARNED
+                    .may_load(deps.storage, &recipient.address)?
+                    .unwrap_or_default();
+                Ok(CommissionEarned {
+                    address: recipient.address,
+                    weight: recipient.weight,
+                    earned,
+                })
+            })
+            .collect::<StdResult<_>>()?;
+
+        Ok(CommissionEarnedResp { recipients })
+    }
+
+    pub fn referral_earnings(
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<ReferralEarningsResp> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start_after = start_after
+            .map(|address| deps.api.addr_validate(&address))
+            .transpose()?;
+        let start = start_after.as_ref().map(Bound::exclusive);
+
+        let referrals = REFERRALS
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| {
+                let (address, referral): (Addr, _) = item?;
+                Ok(ReferralEarnings {
+                    address,
+                    earned: referral.earned,
+                    claimed: referral.claimed,
+                })
+            })
+            .collect::<StdResult<_>>()?;
+
+        Ok(ReferralEarningsResp { referrals })
+    }
+
+    pub fn ownership(deps: Deps) -> StdResult<OwnershipResp> {
+        let state = STATE.load(deps.storage)?;
+        let pending = PENDING_OWNER.may_load(deps.storage)?;
+
+        Ok(OwnershipResp {
+            owner: state.owner,
+            pending_expiry: pending.as_ref().and_then(|pending| pending.expiry),
+            pending_owner: pending.map(|pending| pending.address),
         })
     }
 }
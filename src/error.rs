@@ -1,4 +1,5 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{StdError, Uint128};
+use cw_utils::PaymentError;
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -6,15 +7,36 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    Payment(#[from] PaymentError),
+
     #[error("Unexpected error")]
     Unexpected {},
 
+    #[error("Cannot migrate from a different contract: {contract}")]
+    InvalidContract { contract: String },
+
+    #[error("Cannot migrate from {stored} down to {current}")]
+    Downgrade { stored: String, current: String },
+
     #[error("Unauthorized action")]
     Unauthorized {},
 
+    #[error("There is no pending owner")]
+    NoPendingOwner {},
+
+    #[error("Ownership proposal is expired")]
+    OwnershipProposalExpired {},
+
+    #[error("Cannot renounce ownership of an open bidding without an end time")]
+    OpenEndedBidding {},
+
     #[error("Bid is not enough to beat the max bid")]
     BidTooSmall {},
 
+    #[error("Total bid must be at least {min}")]
+    BidBelowMinimum { min: Uint128 },
+
     #[error("Biding is already closed")]
     BiddingClosed {},
 
@@ -23,4 +45,54 @@ pub enum ContractError {
 
     #[error("There is no bid")]
     BidMissing {},
+
+    #[error("Invalid commission schedule")]
+    InvalidCommission {},
+
+    #[error("Commission recipients must be unique with positive weights summing to 100%")]
+    InvalidCommissionRecipients {},
+
+    #[error("Refunding commissions requires exactly {required} to be sent along")]
+    InvalidRefundFunds { required: Uint128 },
+
+    #[error("Referral share must not exceed 100%")]
+    InvalidReferralShare {},
+
+    #[error("Bidder cannot refer themselves")]
+    InvalidReferrer {},
+
+    #[error("There are no referral rewards to claim")]
+    NoReferralRewards {},
+
+    #[error("Bid does not cover the {commission} commission")]
+    BidBelowCommission { commission: Uint128 },
+
+    #[error("There are no unaccounted {denom} funds to sweep")]
+    NothingToSweep { denom: String },
+
+    #[error("Bidding is paused")]
+    Paused {},
+
+    #[error("Bidding is not accepted in this asset")]
+    InvalidAsset {},
+
+    #[error("Bidding end time must be after its start time")]
+    InvalidTimeRange {},
+
+    #[error("Anti-sniping requires an end time and non-zero window and extension")]
+    InvalidAntiSniping {},
+
+    #[error(
+        "Buy-now price must be positive and not below the reserve price or minimum opening bid"
+    )]
+    InvalidBuyNowPrice {},
+
+    #[error("Minimum increment rate must not exceed 100%")]
+    InvalidMinIncrementRate {},
+
+    #[error("Bidding has not started yet")]
+    BiddingNotStarted {},
+
+    #[error("Bidding time is over")]
+    BiddingEnded {},
 }
@@ -9,45 +9,93 @@ pub mod multitest;
 mod state;
 
 use crate::error::ContractError;
-use crate::msg::InstantiateMsg;
+use crate::msg::{InstantiateMsg, MigrateMsg};
 use cosmwasm_std::{to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use cw_utils::nonpayable;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: InstantiateMsg,
-) -> StdResult<Response> {
-    contract::instantiate(deps, info, msg)
+) -> Result<Response, ContractError> {
+    contract::instantiate(deps, env, info, msg)
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: msg::ExecMsg,
 ) -> Result<Response, ContractError> {
     use contract::exec;
     use msg::ExecMsg::*;
 
+    // native funds are only accepted along with a bid or a cancel refunding commissions
+    if !matches!(msg, Bid { .. } | Cancel { .. }) {
+        nonpayable(&info)?;
+    }
+
     match msg {
-        Bid {} => exec::bid(deps, info),
-        Close {} => exec::close(deps, info),
-        Retract { address } => exec::retract(deps, info, address),
+        Bid { referrer } => exec::bid(deps, env, info, referrer),
+        Close {} => exec::close(deps, env, info),
+        Retract { address, msg } => exec::retract(deps, info, address, msg),
+        Receive(msg) => exec::receive(deps, env, info, msg),
+        ClaimReferral {} => exec::claim_referral(deps, info),
+        Cancel { refund_commissions } => {
+            exec::cancel(deps, info, refund_commissions.unwrap_or_default())
+        }
+        ProposeOwner { new_owner, expiry } => {
+            exec::propose_owner(deps, env, info, new_owner, expiry)
+        }
+        AcceptOwnership {} => exec::accept_ownership(deps, env, info),
+        RenounceOwnership {} => exec::renounce_ownership(deps, info),
+        Pause {} => exec::set_paused(deps, info, true),
+        Unpause {} => exec::set_paused(deps, info, false),
+        Sweep { denom } => exec::sweep(deps, env, info, denom),
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: msg::QueryMsg) -> StdResult<Binary> {
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    contract::migrate(deps, msg)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: msg::QueryMsg) -> StdResult<Binary> {
     use contract::query;
     use msg::QueryMsg::*;
 
     match msg {
         Bid { address } => to_binary(&query::bid(deps, address)?),
+        AllBids { start_after, limit } => to_binary(&query::all_bids(deps, start_after, limit)?),
+        BidHistory {
+            address,
+            start_after,
+            limit,
+        } => to_binary(&query::bid_history(deps, address, start_after, limit)?),
+        RecentBids { start_after, limit } => {
+            to_binary(&query::recent_bids(deps, start_after, limit)?)
+        }
+        TopBids { limit } => to_binary(&query::top_bids(deps, limit)?),
         Highest {} => to_binary(&query::highest(deps)?),
+        MinBidToLead { address } => to_binary(&query::min_bid_to_lead(deps, env, address)?),
+        SimulateBid { address, amount } => {
+            to_binary(&query::simulate_bid(deps, env, address, amount)?)
+        }
         Winner {} => to_binary(&query::winner(deps)?),
         Closed {} => to_binary(&query::closed(deps)?),
+        Status {} => to_binary(&query::status(deps, env)?),
+        Config {} => to_binary(&query::config(deps)?),
+        Paused {} => to_binary(&query::paused(deps)?),
+        Accounting {} => to_binary(&query::accounting(deps, env)?),
+        CommissionPreview { amount } => to_binary(&query::commission_preview(deps, amount)?),
+        CommissionEarned {} => to_binary(&query::commission_earned(deps)?),
+        Ownership {} => to_binary(&query::ownership(deps)?),
+        ReferralEarnings { start_after, limit } => {
+            to_binary(&query::referral_earnings(deps, start_after, limit)?)
+        }
     }
 }
@@ -1,10 +1,57 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{Addr, Binary, Decimal, Timestamp, Uint128};
+use cw20::Cw20ReceiveMsg;
+
+pub use crate::state::{Commission, CommissionRecipient, CommissionSchedule, CommissionTier};
 
 #[cw_serde]
 pub struct InstantiateMsg {
     pub owner: Option<String>,
-    pub commission_percent: Option<u8>,
+    pub guardian: Option<String>,
+    pub beneficiary: Option<String>,
+    pub commission_recipients: Option<Vec<CommissionShare>>,
+    pub asset: AssetInfo,
+    pub commission: Option<Commission>,
+    pub commission_payout: Option<CommissionPayout>,
+    pub referral_share: Option<Decimal>,
+    pub start_time: Option<Timestamp>,
+    pub end_time: Option<Timestamp>,
+    pub anti_sniping: Option<AntiSniping>,
+    pub reserve_price: Option<Uint128>,
+    pub min_opening_bid: Option<Uint128>,
+    pub min_increment: Option<Uint128>,
+    pub min_increment_rate: Option<Decimal>,
+    pub buy_now_price: Option<Uint128>,
+}
+
+#[cw_serde]
+pub struct MigrateMsg {}
+
+#[cw_serde]
+pub enum AssetInfo {
+    Native { denom: String },
+    Cw20 { address: String },
+}
+
+#[cw_serde]
+pub struct CommissionShare {
+    pub address: String,
+    pub weight: Decimal,
+}
+
+#[cw_serde]
+pub enum CommissionPayout {
+    // sent to the commission recipient with every bid
+    Immediate,
+    // accumulated and sent to the commission recipient on close
+    Deferred,
+}
+
+// bids placed within `window` seconds of the end time push it out by `extension` seconds
+#[cw_serde]
+pub struct AntiSniping {
+    pub window: u64,
+    pub extension: u64,
 }
 
 #[cw_serde]
@@ -13,21 +60,106 @@ pub enum QueryMsg {
     #[returns(BidResp)]
     Bid { address: String },
 
+    #[returns(AllBidsResp)]
+    AllBids {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    #[returns(BidHistoryResp)]
+    BidHistory {
+        address: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+
+    #[returns(BidHistoryResp)]
+    RecentBids {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+
+    #[returns(TopBidsResp)]
+    TopBids { limit: Option<u32> },
+
     #[returns(HighestResp)]
     Highest {},
 
+    #[returns(MinBidToLeadResp)]
+    MinBidToLead { address: String },
+
+    #[returns(SimulateBidResp)]
+    SimulateBid { address: String, amount: Uint128 },
+
     #[returns(ClosedResp)]
     Closed {},
 
+    #[returns(StatusResp)]
+    Status {},
+
     #[returns(WinnerResp)]
     Winner {},
+
+    #[returns(ConfigResp)]
+    Config {},
+
+    #[returns(AccountingResp)]
+    Accounting {},
+
+    #[returns(PausedResp)]
+    Paused {},
+
+    #[returns(CommissionPreviewResp)]
+    CommissionPreview { amount: Uint128 },
+
+    #[returns(CommissionEarnedResp)]
+    CommissionEarned {},
+
+    #[returns(OwnershipResp)]
+    Ownership {},
+
+    #[returns(ReferralEarningsResp)]
+    ReferralEarnings {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
 }
 
 #[cw_serde]
 pub enum ExecMsg {
-    Bid {},
+    Bid {
+        referrer: Option<String>,
+    },
     Close {},
-    Retract { address: Option<String> },
+    Retract {
+        address: Option<String>,
+        // executed on the receiver contract along with the retracted funds
+        msg: Option<Binary>,
+    },
+    Receive(Cw20ReceiveMsg),
+    ClaimReferral {},
+    // refunding commissions already paid out requires the owner to send them back along
+    Cancel {
+        refund_commissions: Option<bool>,
+    },
+    ProposeOwner {
+        new_owner: String,
+        expiry: Option<Timestamp>,
+    },
+    AcceptOwnership {},
+    RenounceOwnership {},
+    Pause {},
+    Unpause {},
+    Sweep {
+        denom: String,
+    },
+}
+
+#[cw_serde]
+pub enum ReceiveMsg {
+    Bid { referrer: Option<String> },
+    // cancels refunding the commissions, the sent tokens cover those already paid out
+    Cancel {},
 }
 
 #[cw_serde]
@@ -35,18 +167,163 @@ pub struct BidResp {
     pub bid: Uint128,
 }
 
+#[cw_serde]
+pub struct BidEntry {
+    pub address: Addr,
+    pub retractable: Uint128,
+    pub commission: Uint128,
+    pub total: Uint128,
+}
+
+#[cw_serde]
+pub struct AllBidsResp {
+    pub bids: Vec<BidEntry>,
+}
+
+#[cw_serde]
+pub struct TopBidsResp {
+    pub bids: Vec<BidEntry>,
+}
+
+#[cw_serde]
+pub struct BidRecord {
+    pub sequence: u64,
+    pub bidder: Addr,
+    pub height: u64,
+    pub time: Timestamp,
+    pub amount: Uint128,
+    pub commission: Uint128,
+    pub total: Uint128,
+}
+
+#[cw_serde]
+pub struct BidHistoryResp {
+    pub bids: Vec<BidRecord>,
+}
+
 #[cw_serde]
 pub struct HighestResp {
     pub address: Addr,
     pub amount: Uint128,
 }
 
+#[cw_serde]
+pub struct MinBidToLeadResp {
+    // incoming amount to send, commission included
+    pub amount: Uint128,
+    pub commission: Uint128,
+    pub total: Uint128,
+}
+
+#[cw_serde]
+pub enum SimulateBidResp {
+    Accepted {
+        total: Uint128,
+        commission: Uint128,
+        retractable: Uint128,
+    },
+    Rejected {
+        error: String,
+    },
+}
+
 #[cw_serde]
 pub struct ClosedResp {
     pub closed: bool,
+    pub bought_out: bool,
+    pub cancelled: bool,
+}
+
+#[cw_serde]
+pub enum AuctionStatus {
+    NotStarted,
+    Open,
+    // the end time has passed but nobody closed the bidding yet
+    Closed,
+    Cancelled,
+    Settled,
+}
+
+#[cw_serde]
+pub struct StatusResp {
+    pub status: AuctionStatus,
 }
 
 #[cw_serde]
 pub struct WinnerResp {
     pub winner: Option<HighestResp>,
+    pub reserve_met: bool,
+}
+
+#[cw_serde]
+pub struct ConfigResp {
+    pub owner: Option<Addr>,
+    pub guardian: Option<Addr>,
+    pub beneficiary: Addr,
+    pub commission_recipients: Vec<CommissionRecipient>,
+    pub asset: AssetInfo,
+    pub commission: Commission,
+    pub commission_payout: CommissionPayout,
+    pub commission_total: Uint128,
+    pub commission_dust: Decimal,
+    pub referral_share: Decimal,
+    pub start_time: Option<Timestamp>,
+    pub end_time: Option<Timestamp>,
+    pub anti_sniping: Option<AntiSniping>,
+    pub reserve_price: Option<Uint128>,
+    pub min_opening_bid: Option<Uint128>,
+    pub min_increment: Option<Uint128>,
+    pub min_increment_rate: Option<Decimal>,
+    pub buy_now_price: Option<Uint128>,
+}
+
+#[cw_serde]
+pub struct AccountingResp {
+    pub balance: Uint128,
+    pub total_retractable: Uint128,
+    pub commission_held: Uint128,
+    pub liabilities: Uint128,
+    pub solvent: bool,
+}
+
+#[cw_serde]
+pub struct PausedResp {
+    pub paused: bool,
+}
+
+#[cw_serde]
+pub struct CommissionPreviewResp {
+    pub commission: Uint128,
+    pub retractable: Uint128,
+}
+
+#[cw_serde]
+pub struct CommissionEarned {
+    pub address: Addr,
+    pub weight: Decimal,
+    pub earned: Uint128,
+}
+
+#[cw_serde]
+pub struct CommissionEarnedResp {
+    pub recipients: Vec<CommissionEarned>,
+}
+
+#[cw_serde]
+pub struct ReferralEarnings {
+    pub address: Addr,
+    pub earned: Uint128,
+    pub claimed: Uint128,
+}
+
+#[cw_serde]
+pub struct ReferralEarningsResp {
+    pub referrals: Vec<ReferralEarnings>,
+}
+
+#[cw_serde]
+pub struct OwnershipResp {
+    pub owner: Option<Addr>,
+    pub pending_owner: Option<Addr>,
+    pub pending_expiry: Option<Timestamp>,
 }
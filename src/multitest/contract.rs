@@ -1,10 +1,17 @@
-use cosmwasm_std::{Addr, Coin, StdResult};
+use cosmwasm_std::{to_binary, Addr, Binary, Coin, StdResult, Timestamp, Uint128};
+use cw20::Cw20ExecuteMsg;
 use cw_multi_test::{App, ContractWrapper, Executor};
 
 use crate::error::ContractError;
-use crate::msg::{BidResp, ClosedResp, ExecMsg, HighestResp, InstantiateMsg, QueryMsg, WinnerResp};
-use crate::{execute, instantiate, query};
+use crate::msg::{
+    AccountingResp, AllBidsResp, BidHistoryResp, BidResp, ClosedResp, CommissionEarnedResp,
+    CommissionPreviewResp, ConfigResp, ExecMsg, HighestResp, InstantiateMsg, MigrateMsg,
+    MinBidToLeadResp, OwnershipResp, PausedResp, QueryMsg, ReceiveMsg, ReferralEarningsResp,
+    SimulateBidResp, StatusResp, TopBidsResp, WinnerResp,
+};
+use crate::{execute, instantiate, migrate, query};
 
+#[derive(Debug)]
 pub struct BiddingContract(Addr);
 
 impl BiddingContract {
@@ -13,7 +20,7 @@ impl BiddingContract {
     }
 
     pub fn store_code(app: &mut App) -> u64 {
-        let contract = ContractWrapper::new(execute, instantiate, query);
+        let contract = ContractWrapper::new(execute, instantiate, query).with_migrate(migrate);
         app.store_code(Box::new(contract))
     }
 
@@ -24,18 +31,14 @@ impl BiddingContract {
         sender: &Addr,
         label: &str,
         admin: impl Into<Option<&'a Addr>>,
-        owner: Option<String>,
-        commission_percent: Option<u8>,
-    ) -> StdResult<Self> {
+        msg: &InstantiateMsg,
+    ) -> Result<Self, ContractError> {
         let admin = admin.into();
 
         app.instantiate_contract(
             code_id,
             sender.clone(),
-            &InstantiateMsg {
-                commission_percent,
-                owner,
-            },
+            msg,
             &[],
             label,
             admin.map(Addr::to_string),
@@ -45,12 +48,66 @@ impl BiddingContract {
     }
 
     #[track_caller]
-    pub fn bid(&self, app: &mut App, sender: &Addr, funds: &[Coin]) -> Result<(), ContractError> {
-        app.execute_contract(sender.clone(), self.0.clone(), &ExecMsg::Bid {}, funds)
+    pub fn migrate(&self, app: &mut App, sender: &Addr, code_id: u64) -> Result<(), ContractError> {
+        app.migrate_contract(sender.clone(), self.0.clone(), &MigrateMsg {}, code_id)
             .map_err(|err| err.downcast().unwrap())
             .map(|_| ())
     }
 
+    #[track_caller]
+    pub fn bid(&self, app: &mut App, sender: &Addr, funds: &[Coin]) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::Bid { referrer: None },
+            funds,
+        )
+        .map_err(|err| err.downcast().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn bid_with_referrer(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        funds: &[Coin],
+        referrer: &Addr,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::Bid {
+                referrer: Some(referrer.to_string()),
+            },
+            funds,
+        )
+        .map_err(|err| err.downcast().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn bid_cw20(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        token: &Addr,
+        amount: u128,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            token.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: self.0.to_string(),
+                amount: Uint128::new(amount),
+                msg: to_binary(&ReceiveMsg::Bid { referrer: None })?,
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast().unwrap())
+        .map(|_| ())
+    }
+
     #[track_caller]
     pub fn close(&self, app: &mut App, sender: &Addr) -> Result<(), ContractError> {
         app.execute_contract(sender.clone(), self.0.clone(), &ExecMsg::Close {}, &[])
@@ -68,7 +125,153 @@ impl BiddingContract {
         app.execute_contract(
             sender.clone(),
             self.0.clone(),
-            &ExecMsg::Retract { address },
+            &ExecMsg::Retract { address, msg: None },
+            &[],
+        )
+        .map_err(|err| err.downcast().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn retract_with_hook(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        address: &Addr,
+        msg: Binary,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::Retract {
+                address: Some(address.to_string()),
+                msg: Some(msg),
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn claim_referral(&self, app: &mut App, sender: &Addr) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::ClaimReferral {},
+            &[],
+        )
+        .map_err(|err| err.downcast().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn cancel(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        refund_commissions: Option<bool>,
+        funds: &[Coin],
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::Cancel { refund_commissions },
+            funds,
+        )
+        .map_err(|err| err.downcast().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn cancel_cw20(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        token: &Addr,
+        amount: u128,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            token.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: self.0.to_string(),
+                amount: Uint128::new(amount),
+                msg: to_binary(&ReceiveMsg::Cancel {})?,
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn propose_owner(
+        &self,
+        app: &mut App,
+        sender: &Addr,
+        new_owner: &Addr,
+        expiry: Option<Timestamp>,
+    ) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::ProposeOwner {
+                new_owner: new_owner.to_string(),
+                expiry,
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn accept_ownership(&self, app: &mut App, sender: &Addr) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::AcceptOwnership {},
+            &[],
+        )
+        .map_err(|err| err.downcast().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn renounce_ownership(&self, app: &mut App, sender: &Addr) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::RenounceOwnership {},
+            &[],
+        )
+        .map_err(|err| err.downcast().unwrap())
+        .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn pause(&self, app: &mut App, sender: &Addr) -> Result<(), ContractError> {
+        app.execute_contract(sender.clone(), self.0.clone(), &ExecMsg::Pause {}, &[])
+            .map_err(|err| err.downcast().unwrap())
+            .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn unpause(&self, app: &mut App, sender: &Addr) -> Result<(), ContractError> {
+        app.execute_contract(sender.clone(), self.0.clone(), &ExecMsg::Unpause {}, &[])
+            .map_err(|err| err.downcast().unwrap())
+            .map(|_| ())
+    }
+
+    #[track_caller]
+    pub fn sweep(&self, app: &mut App, sender: &Addr, denom: &str) -> Result<(), ContractError> {
+        app.execute_contract(
+            sender.clone(),
+            self.0.clone(),
+            &ExecMsg::Sweep {
+                denom: denom.to_owned(),
+            },
             &[],
         )
         .map_err(|err| err.downcast().unwrap())
@@ -81,12 +284,80 @@ impl BiddingContract {
             .query_wasm_smart(self.0.clone(), &QueryMsg::Bid { address })
     }
 
+    #[track_caller]
+    pub fn query_all_bids(
+        &self,
+        app: &App,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<AllBidsResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::AllBids { start_after, limit })
+    }
+
+    #[track_caller]
+    pub fn query_bid_history(
+        &self,
+        app: &App,
+        address: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<BidHistoryResp> {
+        app.wrap().query_wasm_smart(
+            self.0.clone(),
+            &QueryMsg::BidHistory {
+                address,
+                start_after,
+                limit,
+            },
+        )
+    }
+
+    #[track_caller]
+    pub fn query_recent_bids(
+        &self,
+        app: &App,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<BidHistoryResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::RecentBids { start_after, limit })
+    }
+
+    #[track_caller]
+    pub fn query_top_bids(&self, app: &App, limit: Option<u32>) -> StdResult<TopBidsResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::TopBids { limit })
+    }
+
     #[track_caller]
     pub fn query_highest_bid(&self, app: &App) -> StdResult<Option<HighestResp>> {
         app.wrap()
             .query_wasm_smart(self.0.clone(), &QueryMsg::Highest {})
     }
 
+    #[track_caller]
+    pub fn query_min_bid_to_lead(&self, app: &App, address: String) -> StdResult<MinBidToLeadResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::MinBidToLead { address })
+    }
+
+    #[track_caller]
+    pub fn query_simulate_bid(
+        &self,
+        app: &App,
+        address: String,
+        amount: u128,
+    ) -> StdResult<SimulateBidResp> {
+        app.wrap().query_wasm_smart(
+            self.0.clone(),
+            &QueryMsg::SimulateBid {
+                address,
+                amount: Uint128::new(amount),
+            },
+        )
+    }
+
     #[track_caller]
     pub fn query_winner(&self, app: &App) -> StdResult<WinnerResp> {
         app.wrap()
@@ -98,6 +369,71 @@ impl BiddingContract {
         app.wrap()
             .query_wasm_smart(self.0.clone(), &QueryMsg::Closed {})
     }
+
+    #[track_caller]
+    pub fn query_status(&self, app: &App) -> StdResult<StatusResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Status {})
+    }
+
+    #[track_caller]
+    pub fn query_commission_preview(
+        &self,
+        app: &App,
+        amount: Uint128,
+    ) -> StdResult<CommissionPreviewResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::CommissionPreview { amount })
+    }
+
+    #[track_caller]
+    pub fn query_commission_earned(&self, app: &App) -> StdResult<CommissionEarnedResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::CommissionEarned {})
+    }
+
+    #[track_caller]
+    pub fn query_referral_earnings(
+        &self,
+        app: &App,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<ReferralEarningsResp> {
+        app.wrap().query_wasm_smart(
+            self.0.clone(),
+            &QueryMsg::ReferralEarnings { start_after, limit },
+        )
+    }
+
+    #[track_caller]
+    pub fn query_ownership(&self, app: &App) -> StdResult<OwnershipResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Ownership {})
+    }
+
+    #[track_caller]
+    pub fn query_paused(&self, app: &App) -> StdResult<PausedResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Paused {})
+    }
+
+    #[track_caller]
+    pub fn query_accounting(&self, app: &App) -> StdResult<AccountingResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Accounting {})
+    }
+
+    #[track_caller]
+    pub fn query_config(&self, app: &App) -> StdResult<ConfigResp> {
+        app.wrap()
+            .query_wasm_smart(self.0.clone(), &QueryMsg::Config {})
+    }
+}
+
+impl From<Addr> for BiddingContract {
+    fn from(addr: Addr) -> Self {
+        BiddingContract(addr)
+    }
 }
 
 impl From<BiddingContract> for Addr {
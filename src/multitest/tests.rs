@@ -1,12 +1,312 @@
 use crate::error::ContractError;
-use crate::msg::{BidResp, HighestResp};
-use cosmwasm_std::{coins, Addr, Uint128};
-use cw_multi_test::App;
+use crate::msg::{
+    AccountingResp, AllBidsResp, AntiSniping, AssetInfo, AuctionStatus, BidEntry, BidHistoryResp,
+    BidRecord, BidResp, ClosedResp, Commission, CommissionEarned, CommissionEarnedResp,
+    CommissionPayout, CommissionPreviewResp, CommissionRecipient, CommissionSchedule,
+    CommissionShare, CommissionTier, ConfigResp, ExecMsg, HighestResp, InstantiateMsg,
+    MinBidToLeadResp, OwnershipResp, PausedResp, ReferralEarnings, ReferralEarningsResp,
+    SimulateBidResp, TopBidsResp, WinnerResp,
+};
+use crate::state::{
+    bids, Asset, Bid, LegacyState, StateV2, StateV3, COMMISSION_EARNED, LEGACY_BIDS, LEGACY_STATE,
+    STATE_V2, STATE_V3,
+};
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    coin, coins, to_binary, Addr, Binary, Coin, Decimal, Deps, DepsMut, Empty, Env, MessageInfo,
+    Response, StdError, StdResult, Uint128,
+};
+use cw2::{set_contract_version, ContractVersion};
+use cw20::{BalanceResponse, Cw20Coin, Cw20QueryMsg, MinterResponse};
+use cw_multi_test::{App, Contract, ContractWrapper, Executor};
+use cw_storage_plus::Map;
+use cw_utils::PaymentError;
 
 use super::contract::BiddingContract;
 
 const ATOM: &str = "ATOM";
 
+fn instantiate_msg() -> InstantiateMsg {
+    InstantiateMsg {
+        owner: None,
+        guardian: None,
+        beneficiary: None,
+        commission_recipients: None,
+        asset: AssetInfo::Native {
+            denom: ATOM.to_owned(),
+        },
+        commission: None,
+        commission_payout: None,
+        referral_share: None,
+        start_time: None,
+        end_time: None,
+        anti_sniping: None,
+        reserve_price: None,
+        min_opening_bid: None,
+        min_increment: None,
+        min_increment_rate: None,
+        buy_now_price: None,
+    }
+}
+
+fn cw20_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        cw20_base::contract::execute,
+        cw20_base::contract::instantiate,
+        cw20_base::contract::query,
+    );
+    Box::new(contract)
+}
+
+fn instantiate_cw20(app: &mut App, minter: &Addr, balances: &[(&Addr, u128)]) -> Addr {
+    let code_id = app.store_code(cw20_contract());
+    app.instantiate_contract(
+        code_id,
+        minter.clone(),
+        &cw20_base::msg::InstantiateMsg {
+            name: "Bidding token".to_owned(),
+            symbol: "BID".to_owned(),
+            decimals: 6,
+            initial_balances: balances
+                .iter()
+                .map(|(address, amount)| Cw20Coin {
+                    address: address.to_string(),
+                    amount: Uint128::new(*amount),
+                })
+                .collect(),
+            mint: Some(MinterResponse {
+                minter: minter.to_string(),
+                cap: None,
+            }),
+            marketing: None,
+        },
+        &[],
+        "Bidding token",
+        None,
+    )
+    .unwrap()
+}
+
+fn cw20_balance(app: &App, token: &Addr, address: &Addr) -> u128 {
+    let resp: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            token,
+            &Cw20QueryMsg::Balance {
+                address: address.to_string(),
+            },
+        )
+        .unwrap();
+    resp.balance.u128()
+}
+
+#[track_caller]
+fn assert_solvent(app: &App, contract: &BiddingContract) {
+    let accounting = contract.query_accounting(app).unwrap();
+    assert_eq!(accounting.balance, accounting.liabilities);
+}
+
+#[cw_serde]
+enum VaultExecMsg {
+    Deposit { beneficiary: String },
+}
+
+#[cw_serde]
+enum VaultQueryMsg {
+    Deposited { beneficiary: String },
+}
+
+// keeps native deposits on behalf of beneficiaries
+fn vault_contract() -> Box<dyn Contract<Empty>> {
+    const DEPOSITS: Map<&str, Uint128> = Map::new("deposits");
+
+    fn instantiate(
+        _deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        _msg: Empty,
+    ) -> StdResult<Response> {
+        Ok(Response::new())
+    }
+
+    fn execute(
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        msg: VaultExecMsg,
+    ) -> StdResult<Response> {
+        let VaultExecMsg::Deposit { beneficiary } = msg;
+        let amount = info.funds.iter().map(|coin| coin.amount).sum::<Uint128>();
+        DEPOSITS.update(deps.storage, &beneficiary, |deposit| -> StdResult<_> {
+            Ok(deposit.unwrap_or_default() + amount)
+        })?;
+
+        Ok(Response::new())
+    }
+
+    fn query(deps: Deps, _env: Env, msg: VaultQueryMsg) -> StdResult<Binary> {
+        let VaultQueryMsg::Deposited { beneficiary } = msg;
+        to_binary(
+            &DEPOSITS
+                .may_load(deps.storage, &beneficiary)?
+                .unwrap_or_default(),
+        )
+    }
+
+    Box::new(ContractWrapper::new(execute, instantiate, query))
+}
+
+// stores the given cw2 version with a 0.1.0 layout bid by the instantiating sender,
+// or a 0.2.0 layout one when asked for that version
+fn legacy_contract() -> Box<dyn Contract<Empty>> {
+    fn instantiate(
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        msg: ContractVersion,
+    ) -> StdResult<Response> {
+        set_contract_version(deps.storage, msg.contract, &msg.version)?;
+
+        let amount = info
+            .funds
+            .first()
+            .map(|coin| coin.amount)
+            .unwrap_or_default();
+        let commission = amount * Uint128::new(10) / Uint128::new(100);
+        let owner = Addr::unchecked("owner");
+        if msg.version == "0.2.0" {
+            STATE_V2.save(
+                deps.storage,
+                &StateV2 {
+                    closed: false,
+                    owner: owner.clone(),
+                    beneficiary: owner.clone(),
+                    commission_recipients: vec![CommissionRecipient {
+                        address: owner.clone(),
+                        weight: Decimal::one(),
+                    }],
+                    asset: Asset::Native(ATOM.to_owned()),
+                    commission_total: commission,
+                    commission: Commission::flat(Decimal::percent(10)),
+                    commission_dust: Decimal::zero(),
+                    referral_share: Decimal::zero(),
+                    commission_payout: CommissionPayout::Deferred,
+                    max_bid: Some((info.sender.clone(), amount)),
+                    start_time: None,
+                    end_time: None,
+                    anti_sniping: None,
+                    reserve_price: None,
+                    min_opening_bid: None,
+                    min_increment: None,
+                    min_increment_rate: None,
+                    buy_now_price: None,
+                    bought_out: false,
+                },
+            )?;
+            COMMISSION_EARNED.save(deps.storage, &owner, &commission)?;
+        } else if msg.version == "0.3.0" {
+            STATE_V3.save(
+                deps.storage,
+                &StateV3 {
+                    closed: false,
+                    owner: Some(owner.clone()),
+                    guardian: None,
+                    paused: false,
+                    beneficiary: owner.clone(),
+                    commission_recipients: vec![CommissionRecipient {
+                        address: owner.clone(),
+                        weight: Decimal::one(),
+                    }],
+                    asset: Asset::Native(ATOM.to_owned()),
+                    commission_total: commission,
+                    commission: Commission::flat(Decimal::percent(10)),
+                    commission_dust: Decimal::zero(),
+                    referral_share: Decimal::zero(),
+                    commission_payout: CommissionPayout::Deferred,
+                    max_bid: Some((info.sender.clone(), amount)),
+                    start_time: None,
+                    end_time: None,
+                    anti_sniping: None,
+                    reserve_price: None,
+                    min_opening_bid: None,
+                    min_increment: None,
+                    min_increment_rate: None,
+                    buy_now_price: None,
+                    bought_out: false,
+                    cancelled: false,
+                    commissions_refunded: false,
+                },
+            )?;
+            COMMISSION_EARNED.save(deps.storage, &owner, &commission)?;
+            // 0.3.0 already keeps bids indexed by their total
+            bids().save(
+                deps.storage,
+                &info.sender,
+                &Bid {
+                    retractable: amount - commission,
+                    commission,
+                },
+            )?;
+            return Ok(Response::new());
+        } else {
+            LEGACY_STATE.save(
+                deps.storage,
+                &LegacyState {
+                    closed: false,
+                    owner,
+                    commission_total: commission,
+                    commission_percent: 10,
+                    max_bid: Some((info.sender.clone(), amount)),
+                },
+            )?;
+        }
+        LEGACY_BIDS.save(
+            deps.storage,
+            &info.sender,
+            &Bid {
+                retractable: amount - commission,
+                commission,
+            },
+        )?;
+
+        Ok(Response::new())
+    }
+
+    fn execute(_deps: DepsMut, _env: Env, _info: MessageInfo, _msg: Empty) -> StdResult<Response> {
+        Err(StdError::generic_err("Not supported"))
+    }
+
+    fn query(_deps: Deps, _env: Env, _msg: Empty) -> StdResult<Binary> {
+        Err(StdError::generic_err("Not supported"))
+    }
+
+    Box::new(ContractWrapper::new(execute, instantiate, query))
+}
+
+fn instantiate_legacy(
+    app: &mut App,
+    sender: &Addr,
+    admin: &Addr,
+    contract: &str,
+    version: &str,
+    funds: &[Coin],
+) -> BiddingContract {
+    let code_id = app.store_code(legacy_contract());
+    app.instantiate_contract(
+        code_id,
+        sender.clone(),
+        &ContractVersion {
+            contract: contract.to_owned(),
+            version: version.to_owned(),
+        },
+        funds,
+        "Legacy contract",
+        Some(admin.to_string()),
+    )
+    .unwrap()
+    .into()
+}
+
 #[test]
 fn query_bid() {
     let owner = Addr::unchecked("owner");
@@ -19,8 +319,7 @@ fn query_bid() {
         &owner,
         "Bidding contract",
         None,
-        None,
-        None,
+        &instantiate_msg(),
     )
     .unwrap();
 
@@ -35,9 +334,93 @@ fn query_bid() {
 }
 
 #[test]
-fn query_closed() {
+fn query_all_bids() {
     let owner = Addr::unchecked("owner");
-    let mut app = App::default();
+    let senders: Vec<_> = (1..=3)
+        .map(|i| Addr::unchecked(format!("sender{}", i)))
+        .collect();
+    let mut app = App::new(|router, _api, storage| {
+        for sender in &senders {
+            router
+                .bank
+                .init_balance(storage, sender, coins(100, ATOM))
+                .unwrap();
+        }
+    });
+    let code_id = BiddingContract::store_code(&mut app);
+
+    let contract = BiddingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Bidding contract",
+        None,
+        &instantiate_msg(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        contract.query_all_bids(&app, None, None).unwrap(),
+        AllBidsResp { bids: vec![] }
+    );
+
+    for (i, sender) in senders.iter().enumerate() {
+        contract
+            .bid(&mut app, sender, &coins(20 * (i as u128 + 1), ATOM))
+            .unwrap();
+    }
+
+    let resp = contract.query_all_bids(&app, None, Some(2)).unwrap();
+    assert_eq!(
+        resp,
+        AllBidsResp {
+            bids: vec![
+                BidEntry {
+                    address: senders[0].clone(),
+                    retractable: Uint128::new(19),
+                    commission: Uint128::new(1),
+                    total: Uint128::new(20),
+                },
+                BidEntry {
+                    address: senders[1].clone(),
+                    retractable: Uint128::new(38),
+                    commission: Uint128::new(2),
+                    total: Uint128::new(40),
+                },
+            ]
+        }
+    );
+
+    let resp = contract
+        .query_all_bids(&app, Some(senders[1].to_string()), None)
+        .unwrap();
+    assert_eq!(
+        resp,
+        AllBidsResp {
+            bids: vec![BidEntry {
+                address: senders[2].clone(),
+                retractable: Uint128::new(57),
+                commission: Uint128::new(3),
+                total: Uint128::new(60),
+            }]
+        }
+    );
+}
+
+#[test]
+fn query_top_bids() {
+    let owner = Addr::unchecked("owner");
+    let sender1 = Addr::unchecked("sender1");
+    let sender2 = Addr::unchecked("sender2");
+    let sender3 = Addr::unchecked("sender3");
+    let mut app = App::new(|router, _api, storage| {
+        for sender in [&sender1, &sender2, &sender3] {
+            router
+                .bank
+                .init_balance(storage, sender, coins(100, ATOM))
+                .unwrap();
+        }
+    });
     let code_id = BiddingContract::store_code(&mut app);
 
     let contract = BiddingContract::instantiate(
@@ -46,16 +429,200 @@ fn query_closed() {
         &owner,
         "Bidding contract",
         None,
+        &instantiate_msg(),
+    )
+    .unwrap();
+
+    contract.bid(&mut app, &sender1, &coins(20, ATOM)).unwrap();
+    contract.bid(&mut app, &sender2, &coins(40, ATOM)).unwrap();
+    contract.bid(&mut app, &sender3, &coins(60, ATOM)).unwrap();
+    // raising moves the bidder back to the top
+    contract.bid(&mut app, &sender1, &coins(60, ATOM)).unwrap();
+
+    let ranking = |resp: TopBidsResp| {
+        resp.bids
+            .into_iter()
+            .map(|bid| (bid.address, bid.total.u128()))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        ranking(contract.query_top_bids(&app, None).unwrap()),
+        vec![
+            (sender1.clone(), 80),
+            (sender3.clone(), 60),
+            (sender2.clone(), 40)
+        ]
+    );
+    assert_eq!(
+        ranking(contract.query_top_bids(&app, Some(1)).unwrap()),
+        vec![(sender1.clone(), 80)]
+    );
+
+    // retracted bids drop out of the ranking
+    contract.close(&mut app, &owner).unwrap();
+    contract.retract(&mut app, &sender3, None).unwrap();
+    assert_eq!(
+        ranking(contract.query_top_bids(&app, None).unwrap()),
+        vec![(sender2, 40)]
+    );
+}
+
+#[test]
+fn query_bid_history() {
+    let owner = Addr::unchecked("owner");
+    let sender1 = Addr::unchecked("sender1");
+    let sender2 = Addr::unchecked("sender2");
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender1, coins(100, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &sender2, coins(100, ATOM))
+            .unwrap();
+    });
+    let code_id = BiddingContract::store_code(&mut app);
+
+    let contract = BiddingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Bidding contract",
         None,
+        &instantiate_msg(),
+    )
+    .unwrap();
+
+    contract.bid(&mut app, &sender1, &coins(20, ATOM)).unwrap();
+    let first_block = app.block_info();
+    app.update_block(|block| {
+        block.height += 1;
+        block.time = block.time.plus_seconds(5);
+    });
+    contract.bid(&mut app, &sender2, &coins(40, ATOM)).unwrap();
+    contract.bid(&mut app, &sender1, &coins(40, ATOM)).unwrap();
+    let block = app.block_info();
+
+    let first = BidRecord {
+        sequence: 1,
+        bidder: sender1.clone(),
+        height: first_block.height,
+        time: first_block.time,
+        amount: Uint128::new(20),
+        commission: Uint128::new(1),
+        total: Uint128::new(20),
+    };
+    let raise = BidRecord {
+        sequence: 3,
+        bidder: sender1.clone(),
+        height: block.height,
+        time: block.time,
+        amount: Uint128::new(40),
+        commission: Uint128::new(2),
+        total: Uint128::new(60),
+    };
+
+    assert_eq!(
+        contract
+            .query_bid_history(&app, sender1.to_string(), None, None)
+            .unwrap(),
+        BidHistoryResp {
+            bids: vec![first.clone(), raise.clone()]
+        }
+    );
+    assert_eq!(
+        contract
+            .query_bid_history(&app, sender1.to_string(), Some(1), None)
+            .unwrap(),
+        BidHistoryResp {
+            bids: vec![raise.clone()]
+        }
+    );
+
+    let sequences = |resp: BidHistoryResp| {
+        resp.bids
+            .into_iter()
+            .map(|bid| bid.sequence)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        sequences(contract.query_recent_bids(&app, None, Some(2)).unwrap()),
+        vec![3, 2]
+    );
+    assert_eq!(
+        sequences(contract.query_recent_bids(&app, Some(2), None).unwrap()),
+        vec![1]
+    );
+}
+
+#[test]
+fn query_closed() {
+    let owner = Addr::unchecked("owner");
+    let mut app = App::default();
+    let code_id = BiddingContract::store_code(&mut app);
+
+    let contract = BiddingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Bidding contract",
         None,
+        &instantiate_msg(),
     )
     .unwrap();
 
-    assert_eq!(contract.query_closed(&app).unwrap().closed, false);
+    assert!(!contract.query_closed(&app).unwrap().closed);
 
     contract.close(&mut app, &owner).unwrap();
 
-    assert_eq!(contract.query_closed(&app).unwrap().closed, true);
+    assert!(contract.query_closed(&app).unwrap().closed);
+}
+
+#[test]
+fn query_config() {
+    let owner = Addr::unchecked("owner");
+    let mut app = App::default();
+    let code_id = BiddingContract::store_code(&mut app);
+
+    let contract = BiddingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Bidding contract",
+        None,
+        &instantiate_msg(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        contract.query_config(&app).unwrap(),
+        ConfigResp {
+            owner: Some(owner.clone()),
+            guardian: None,
+            beneficiary: owner.clone(),
+            commission_recipients: vec![CommissionRecipient {
+                address: owner,
+                weight: Decimal::one(),
+            }],
+            asset: AssetInfo::Native {
+                denom: ATOM.to_owned()
+            },
+            commission: Commission::flat(Decimal::percent(5)),
+            commission_payout: CommissionPayout::Immediate,
+            commission_total: Uint128::zero(),
+            commission_dust: Decimal::zero(),
+            referral_share: Decimal::zero(),
+            start_time: None,
+            end_time: None,
+            anti_sniping: None,
+            reserve_price: None,
+            min_opening_bid: None,
+            min_increment: None,
+            min_increment_rate: None,
+            buy_now_price: None,
+        }
+    );
 }
 
 #[test]
@@ -81,8 +648,7 @@ fn query_highest_bid() {
         &owner,
         "Bidding contract",
         None,
-        None,
-        None,
+        &instantiate_msg(),
     )
     .unwrap();
 
@@ -128,8 +694,7 @@ fn query_winner() {
         &owner,
         "Bidding contract",
         None,
-        None,
-        None,
+        &instantiate_msg(),
     )
     .unwrap();
 
@@ -175,8 +740,7 @@ fn bid_with_funds() {
         &owner,
         "Bidding contract",
         None,
-        None,
-        None,
+        &instantiate_msg(),
     )
     .unwrap();
 
@@ -218,8 +782,7 @@ fn bid_too_small() {
         &owner,
         "Bidding contract",
         None,
-        None,
-        None,
+        &instantiate_msg(),
     )
     .unwrap();
 
@@ -272,8 +835,7 @@ fn close() {
         &owner,
         "Bidding contract",
         None,
-        None,
-        None,
+        &instantiate_msg(),
     )
     .unwrap();
 
@@ -294,13 +856,14 @@ fn close() {
     let err = contract.close(&mut app, &owner).unwrap_err();
     assert_eq!(err, ContractError::BiddingClosed {});
 
+    // the winning bid of 11 goes to the owner
     let balance1 = app
         .wrap()
         .query_balance(sender1.clone(), ATOM)
         .unwrap()
         .amount
         .u128();
-    assert_eq!(balance1, 20);
+    assert_eq!(balance1, 9);
     let balance2 = app
         .wrap()
         .query_balance(sender2, ATOM)
@@ -308,6 +871,8 @@ fn close() {
         .amount
         .u128();
     assert_eq!(balance2, 10);
+    let owner_balance = app.wrap().query_balance(owner, ATOM).unwrap().amount.u128();
+    assert_eq!(owner_balance, 11);
 
     let err = contract.retract(&mut app, &sender1, None).unwrap_err();
     assert_eq!(err, ContractError::BidMissing {});
@@ -344,32 +909,48 @@ fn retract() {
         &owner,
         "Bidding contract",
         None,
-        None,
-        Some(10),
+        &InstantiateMsg {
+            commission: Some(Commission::flat(Decimal::percent(10))),
+            ..instantiate_msg()
+        },
     )
     .unwrap();
 
     // total commission: 10+20+30 * 10% = 6
     contract.bid(&mut app, &sender1, &coins(10, ATOM)).unwrap();
+    assert_solvent(&app, &contract);
     contract.bid(&mut app, &sender2, &coins(20, ATOM)).unwrap();
+    assert_solvent(&app, &contract);
     contract.bid(&mut app, &sender3, &coins(30, ATOM)).unwrap();
+    assert_solvent(&app, &contract);
 
     let err = contract.retract(&mut app, &sender1, None).unwrap_err();
     assert_eq!(err, ContractError::BiddingNotClosed {});
 
-    // 30 initial balance - (3 commission from 30 bid) + 6 total commission
+    // 30 initial balance - 30 winning bid
     contract.close(&mut app, &owner).unwrap();
+    assert_solvent(&app, &contract);
     assert_eq!(
         app.wrap()
             .query_balance(sender3.clone(), ATOM)
             .unwrap()
             .amount
             .u128(),
+        0
+    );
+    // 27 winning bid without commission + 6 total commission
+    assert_eq!(
+        app.wrap()
+            .query_balance(owner.clone(), ATOM)
+            .unwrap()
+            .amount
+            .u128(),
         33
     );
 
     // 30 initial balance - 1 commission from 10 bid
     contract.retract(&mut app, &sender1, None).unwrap();
+    assert_solvent(&app, &contract);
     assert_eq!(
         app.wrap()
             .query_balance(sender1.clone(), ATOM)
@@ -383,6 +964,7 @@ fn retract() {
     contract
         .retract(&mut app, &sender2, Some(receiver.to_string()))
         .unwrap();
+    assert_solvent(&app, &contract);
     assert_eq!(
         app.wrap()
             .query_balance(receiver.clone(), ATOM)
@@ -395,3 +977,2455 @@ fn retract() {
     let err = contract.retract(&mut app, &sender1, None).unwrap_err();
     assert_eq!(err, ContractError::BidMissing {});
 }
+
+#[test]
+fn custom_denom() {
+    let owner = Addr::unchecked("owner");
+    let sender1 = Addr::unchecked("sender1");
+    let sender2 = Addr::unchecked("sender2");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender1, vec![coin(20, "ujuno"), coin(20, ATOM)])
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &sender2, coins(20, "ujuno"))
+            .unwrap();
+    });
+    let code_id = BiddingContract::store_code(&mut app);
+
+    let contract = BiddingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Bidding contract",
+        None,
+        &InstantiateMsg {
+            asset: AssetInfo::Native {
+                denom: "ujuno".to_owned(),
+            },
+            commission: Some(Commission::flat(Decimal::percent(10))),
+            ..instantiate_msg()
+        },
+    )
+    .unwrap();
+
+    let err = contract
+        .bid(&mut app, &sender1, &coins(10, ATOM))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Payment(PaymentError::MissingDenom("ujuno".to_owned()))
+    );
+
+    contract
+        .bid(&mut app, &sender1, &coins(10, "ujuno"))
+        .unwrap();
+    contract
+        .bid(&mut app, &sender2, &coins(20, "ujuno"))
+        .unwrap();
+    contract.close(&mut app, &owner).unwrap();
+    contract.retract(&mut app, &sender1, None).unwrap();
+
+    // 20 initial balance - 10 bid + 9 retracted
+    assert_eq!(
+        app.wrap()
+            .query_balance(sender1.clone(), "ujuno")
+            .unwrap()
+            .amount
+            .u128(),
+        19
+    );
+    assert_eq!(
+        app.wrap()
+            .query_balance(sender1, ATOM)
+            .unwrap()
+            .amount
+            .u128(),
+        20
+    );
+    // 18 winning bid without commission + 3 total commission
+    assert_eq!(
+        app.wrap()
+            .query_balance(owner, "ujuno")
+            .unwrap()
+            .amount
+            .u128(),
+        21
+    );
+}
+
+#[test]
+fn cw20_bidding() {
+    let owner = Addr::unchecked("owner");
+    let sender1 = Addr::unchecked("sender1");
+    let sender2 = Addr::unchecked("sender2");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender1, coins(20, ATOM))
+            .unwrap();
+    });
+    let token = instantiate_cw20(&mut app, &owner, &[(&sender1, 20), (&sender2, 20)]);
+    let other_token = instantiate_cw20(&mut app, &owner, &[(&sender1, 20)]);
+    let code_id = BiddingContract::store_code(&mut app);
+
+    let contract = BiddingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Bidding contract",
+        None,
+        &InstantiateMsg {
+            asset: AssetInfo::Cw20 {
+                address: token.to_string(),
+            },
+            commission: Some(Commission::flat(Decimal::percent(10))),
+            ..instantiate_msg()
+        },
+    )
+    .unwrap();
+
+    let err = contract
+        .bid(&mut app, &sender1, &coins(10, ATOM))
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidAsset {});
+
+    let err = contract
+        .bid_cw20(&mut app, &sender1, &other_token, 10)
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidAsset {});
+
+    contract.bid_cw20(&mut app, &sender1, &token, 10).unwrap();
+    assert_solvent(&app, &contract);
+    contract.bid_cw20(&mut app, &sender2, &token, 20).unwrap();
+    assert_solvent(&app, &contract);
+
+    let resp = contract.query_bid(&app, sender2.to_string()).unwrap();
+    assert_eq!(
+        resp,
+        BidResp {
+            bid: Uint128::new(20)
+        }
+    );
+    // 3 total commission is already paid out to the owner
+    assert_eq!(cw20_balance(&app, &token, contract.addr()), 27);
+    assert_eq!(cw20_balance(&app, &token, &owner), 3);
+
+    contract.close(&mut app, &owner).unwrap();
+    assert_solvent(&app, &contract);
+    contract.retract(&mut app, &sender1, None).unwrap();
+    assert_solvent(&app, &contract);
+
+    // 20 initial balance - 10 bid + 9 retracted
+    assert_eq!(cw20_balance(&app, &token, &sender1), 19);
+    assert_eq!(cw20_balance(&app, &token, &sender2), 0);
+    // 18 winning bid without commission + 3 total commission
+    assert_eq!(cw20_balance(&app, &token, &owner), 21);
+    assert_eq!(cw20_balance(&app, &token, contract.addr()), 0);
+}
+
+#[test]
+fn bidding_window() {
+    let owner = Addr::unchecked("owner");
+    let sender1 = Addr::unchecked("sender1");
+    let sender2 = Addr::unchecked("sender2");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender1, coins(20, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &sender2, coins(20, ATOM))
+            .unwrap();
+    });
+    let code_id = BiddingContract::store_code(&mut app);
+    let now = app.block_info().time;
+
+    let err = BiddingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Bidding contract",
+        None,
+        &InstantiateMsg {
+            start_time: Some(now.plus_seconds(100)),
+            end_time: Some(now.plus_seconds(100)),
+            ..instantiate_msg()
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidTimeRange {});
+
+    let contract = BiddingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Bidding contract",
+        None,
+        &InstantiateMsg {
+            start_time: Some(now.plus_seconds(100)),
+            end_time: Some(now.plus_seconds(200)),
+            ..instantiate_msg()
+        },
+    )
+    .unwrap();
+
+    let err = contract
+        .bid(&mut app, &sender1, &coins(10, ATOM))
+        .unwrap_err();
+    assert_eq!(err, ContractError::BiddingNotStarted {});
+    assert_eq!(
+        contract.query_status(&app).unwrap().status,
+        AuctionStatus::NotStarted
+    );
+
+    app.update_block(|block| block.time = block.time.plus_seconds(100));
+    assert_eq!(
+        contract.query_status(&app).unwrap().status,
+        AuctionStatus::Open
+    );
+    contract.bid(&mut app, &sender1, &coins(10, ATOM)).unwrap();
+    contract.bid(&mut app, &sender2, &coins(15, ATOM)).unwrap();
+
+    // only the owner can close before the end time
+    let err = contract.close(&mut app, &sender1).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    app.update_block(|block| block.time = block.time.plus_seconds(100));
+    let err = contract
+        .bid(&mut app, &sender1, &coins(10, ATOM))
+        .unwrap_err();
+    assert_eq!(err, ContractError::BiddingEnded {});
+    assert_eq!(
+        contract.query_status(&app).unwrap().status,
+        AuctionStatus::Closed
+    );
+
+    // anyone can close once the end time has passed
+    contract.close(&mut app, &sender1).unwrap();
+    assert_eq!(
+        contract.query_status(&app).unwrap().status,
+        AuctionStatus::Settled
+    );
+    contract.retract(&mut app, &sender1, None).unwrap();
+
+    let resp = contract.query_winner(&app).unwrap().winner;
+    assert_eq!(
+        resp,
+        Some(HighestResp {
+            address: sender2,
+            amount: Uint128::new(15)
+        })
+    );
+}
+
+#[test]
+fn anti_sniping() {
+    let owner = Addr::unchecked("owner");
+    let sender1 = Addr::unchecked("sender1");
+    let sender2 = Addr::unchecked("sender2");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender1, coins(20, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &sender2, coins(20, ATOM))
+            .unwrap();
+    });
+    let code_id = BiddingContract::store_code(&mut app);
+    let now = app.block_info().time;
+
+    let err = BiddingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Bidding contract",
+        None,
+        &InstantiateMsg {
+            anti_sniping: Some(AntiSniping {
+                window: 10,
+                extension: 30,
+            }),
+            ..instantiate_msg()
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidAntiSniping {});
+
+    let contract = BiddingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Bidding contract",
+        None,
+        &InstantiateMsg {
+            end_time: Some(now.plus_seconds(100)),
+            anti_sniping: Some(AntiSniping {
+                window: 10,
+                extension: 30,
+            }),
+            ..instantiate_msg()
+        },
+    )
+    .unwrap();
+
+    // outside of the window the end time stays
+    contract.bid(&mut app, &sender1, &coins(5, ATOM)).unwrap();
+    assert_eq!(
+        contract.query_config(&app).unwrap().end_time,
+        Some(now.plus_seconds(100))
+    );
+
+    app.update_block(|block| block.time = block.time.plus_seconds(95));
+    let resp = app
+        .execute_contract(
+            sender2.clone(),
+            contract.addr().clone(),
+            &ExecMsg::Bid { referrer: None },
+            &coins(10, ATOM),
+        )
+        .unwrap();
+    let event = resp
+        .events
+        .iter()
+        .find(|event| event.ty == "wasm-auction_extended")
+        .unwrap();
+    assert!(event
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "end_time"
+            && attr.value == now.plus_seconds(130).seconds().to_string()));
+    assert_eq!(
+        contract.query_config(&app).unwrap().end_time,
+        Some(now.plus_seconds(130))
+    );
+
+    // the original end time has passed, but bidding is still open
+    app.update_block(|block| block.time = block.time.plus_seconds(25));
+    contract.bid(&mut app, &sender1, &coins(10, ATOM)).unwrap();
+    assert_eq!(
+        contract.query_config(&app).unwrap().end_time,
+        Some(now.plus_seconds(160))
+    );
+
+    app.update_block(|block| block.time = block.time.plus_seconds(40));
+    let err = contract
+        .bid(&mut app, &sender2, &coins(10, ATOM))
+        .unwrap_err();
+    assert_eq!(err, ContractError::BiddingEnded {});
+}
+
+#[test]
+fn reserve_price() {
+    let owner = Addr::unchecked("owner");
+    let sender1 = Addr::unchecked("sender1");
+    let sender2 = Addr::unchecked("sender2");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender1, coins(20, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &sender2, coins(20, ATOM))
+            .unwrap();
+    });
+    let code_id = BiddingContract::store_code(&mut app);
+
+    let contract = BiddingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Bidding contract",
+        None,
+        &InstantiateMsg {
+            commission: Some(Commission::flat(Decimal::percent(10))),
+            reserve_price: Some(Uint128::new(30)),
+            ..instantiate_msg()
+        },
+    )
+    .unwrap();
+
+    contract.bid(&mut app, &sender1, &coins(10, ATOM)).unwrap();
+    contract.bid(&mut app, &sender2, &coins(20, ATOM)).unwrap();
+    contract.close(&mut app, &owner).unwrap();
+
+    assert_eq!(
+        contract.query_winner(&app).unwrap(),
+        WinnerResp {
+            winner: None,
+            reserve_met: false
+        }
+    );
+
+    // the highest bidder retracts like everyone else
+    contract.retract(&mut app, &sender2, None).unwrap();
+    contract.retract(&mut app, &sender1, None).unwrap();
+
+    // 20 initial balance - 2 commission from 20 bid
+    assert_eq!(
+        app.wrap()
+            .query_balance(sender2, ATOM)
+            .unwrap()
+            .amount
+            .u128(),
+        18
+    );
+    // 20 initial balance - 1 commission from 10 bid
+    assert_eq!(
+        app.wrap()
+            .query_balance(sender1, ATOM)
+            .unwrap()
+            .amount
+            .u128(),
+        19
+    );
+    // 3 total commission
+    assert_eq!(
+        app.wrap().query_balance(owner, ATOM).unwrap().amount.u128(),
+        3
+    );
+}
+
+#[test]
+fn bid_increment() {
+    let owner = Addr::unchecked("owner");
+    let sender1 = Addr::unchecked("sender1");
+    let sender2 = Addr::unchecked("sender2");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender1, coins(200, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &sender2, coins(200, ATOM))
+            .unwrap();
+    });
+    let code_id = BiddingContract::store_code(&mut app);
+
+    let err = BiddingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Bidding contract",
+        None,
+        &InstantiateMsg {
+            min_increment_rate: Some(Decimal::percent(101)),
+            ..instantiate_msg()
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidMinIncrementRate {});
+
+    let contract = BiddingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Bidding contract",
+        None,
+        &InstantiateMsg {
+            min_opening_bid: Some(Uint128::new(10)),
+            min_increment: Some(Uint128::new(5)),
+            min_increment_rate: Some(Decimal::percent(10)),
+            ..instantiate_msg()
+        },
+    )
+    .unwrap();
+
+    let err = contract
+        .bid(&mut app, &sender1, &coins(9, ATOM))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::BidBelowMinimum {
+            min: Uint128::new(10)
+        }
+    );
+    contract.bid(&mut app, &sender1, &coins(10, ATOM)).unwrap();
+
+    // absolute increment is bigger: 10 + 5
+    let err = contract
+        .bid(&mut app, &sender2, &coins(14, ATOM))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::BidBelowMinimum {
+            min: Uint128::new(15)
+        }
+    );
+    contract.bid(&mut app, &sender2, &coins(15, ATOM)).unwrap();
+
+    // raising own bid counts the total: 10 + 5 = 15 is not above 15
+    let err = contract
+        .bid(&mut app, &sender1, &coins(5, ATOM))
+        .unwrap_err();
+    assert_eq!(err, ContractError::BidTooSmall {});
+    contract.bid(&mut app, &sender1, &coins(90, ATOM)).unwrap();
+
+    // relative increment is bigger: 100 + 10%
+    let err = contract
+        .bid(&mut app, &sender2, &coins(94, ATOM))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::BidBelowMinimum {
+            min: Uint128::new(110)
+        }
+    );
+    contract.bid(&mut app, &sender2, &coins(95, ATOM)).unwrap();
+
+    let resp = contract.query_highest_bid(&app).unwrap();
+    assert_eq!(
+        resp,
+        Some(HighestResp {
+            address: sender2,
+            amount: Uint128::new(110)
+        })
+    );
+}
+
+#[test]
+fn min_bid_to_lead() {
+    let owner = Addr::unchecked("owner");
+    let sender1 = Addr::unchecked("sender1");
+    let sender2 = Addr::unchecked("sender2");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender1, coins(100, ATOM))
+            .unwrap();
+    });
+    let code_id = BiddingContract::store_code(&mut app);
+
+    let contract = BiddingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Bidding contract",
+        None,
+        &InstantiateMsg {
+            min_opening_bid: Some(Uint128::new(10)),
+            min_increment: Some(Uint128::new(2)),
+            commission: Some(Commission {
+                schedule: CommissionSchedule::Flat {
+                    rate: Decimal::percent(10),
+                },
+                min: Some(Uint128::new(3)),
+                max: None,
+            }),
+            ..instantiate_msg()
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        contract
+            .query_min_bid_to_lead(&app, sender1.to_string())
+            .unwrap(),
+        MinBidToLeadResp {
+            amount: Uint128::new(10),
+            commission: Uint128::new(3),
+            total: Uint128::new(10),
+        }
+    );
+    assert_eq!(
+        contract
+            .query_simulate_bid(&app, sender1.to_string(), 9)
+            .unwrap(),
+        SimulateBidResp::Rejected {
+            error: ContractError::BidBelowMinimum {
+                min: Uint128::new(10)
+            }
+            .to_string()
+        }
+    );
+    assert_eq!(
+        contract
+            .query_simulate_bid(&app, owner.to_string(), 10)
+            .unwrap(),
+        SimulateBidResp::Rejected {
+            error: ContractError::Unauthorized {}.to_string()
+        }
+    );
+
+    contract.bid(&mut app, &sender1, &coins(10, ATOM)).unwrap();
+
+    assert_eq!(
+        contract
+            .query_min_bid_to_lead(&app, sender2.to_string())
+            .unwrap(),
+        MinBidToLeadResp {
+            amount: Uint128::new(12),
+            commission: Uint128::new(3),
+            total: Uint128::new(12),
+        }
+    );
+
+    // raising by the 2 increment does not cover the 3 minimum commission
+    assert_eq!(
+        contract
+            .query_simulate_bid(&app, sender1.to_string(), 2)
+            .unwrap(),
+        SimulateBidResp::Rejected {
+            error: ContractError::BidBelowCommission {
+                commission: Uint128::new(3)
+            }
+            .to_string()
+        }
+    );
+    let resp = contract
+        .query_min_bid_to_lead(&app, sender1.to_string())
+        .unwrap();
+    assert_eq!(
+        resp,
+        MinBidToLeadResp {
+            amount: Uint128::new(3),
+            commission: Uint128::new(3),
+            total: Uint128::new(13),
+        }
+    );
+    assert_eq!(
+        contract
+            .query_simulate_bid(&app, sender1.to_string(), 3)
+            .unwrap(),
+        SimulateBidResp::Accepted {
+            total: Uint128::new(13),
+            commission: Uint128::new(3),
+            retractable: Uint128::zero(),
+        }
+    );
+
+    contract
+        .bid(&mut app, &sender1, &coins(resp.amount.u128(), ATOM))
+        .unwrap();
+    assert_eq!(
+        contract.query_highest_bid(&app).unwrap(),
+        Some(HighestResp {
+            address: sender1,
+            amount: Uint128::new(13)
+        })
+    );
+
+    // no amount leads when bidding is impossible
+    let err = contract
+        .query_min_bid_to_lead(&app, owner.to_string())
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains(&ContractError::Unauthorized {}.to_string()));
+
+    contract.pause(&mut app, &owner).unwrap();
+    let err = contract
+        .query_min_bid_to_lead(&app, sender2.to_string())
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains(&ContractError::Paused {}.to_string()));
+    contract.unpause(&mut app, &owner).unwrap();
+
+    contract.close(&mut app, &owner).unwrap();
+    let err = contract
+        .query_min_bid_to_lead(&app, sender2.to_string())
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains(&ContractError::BiddingClosed {}.to_string()));
+}
+
+#[test]
+fn buy_now() {
+    let owner = Addr::unchecked("owner");
+    let sender1 = Addr::unchecked("sender1");
+    let sender2 = Addr::unchecked("sender2");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender1, coins(50, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &sender2, coins(50, ATOM))
+            .unwrap();
+    });
+    let code_id = BiddingContract::store_code(&mut app);
+
+    let err = BiddingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Bidding contract",
+        None,
+        &InstantiateMsg {
+            reserve_price: Some(Uint128::new(50)),
+            buy_now_price: Some(Uint128::new(40)),
+            ..instantiate_msg()
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidBuyNowPrice {});
+
+    let err = BiddingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Bidding contract",
+        None,
+        &InstantiateMsg {
+            min_opening_bid: Some(Uint128::new(20)),
+            buy_now_price: Some(Uint128::new(10)),
+            ..instantiate_msg()
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidBuyNowPrice {});
+
+    let contract = BiddingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Bidding contract",
+        None,
+        &InstantiateMsg {
+            commission: Some(Commission::flat(Decimal::percent(10))),
+            buy_now_price: Some(Uint128::new(40)),
+            ..instantiate_msg()
+        },
+    )
+    .unwrap();
+
+    contract.bid(&mut app, &sender1, &coins(20, ATOM)).unwrap();
+    assert_solvent(&app, &contract);
+    assert_eq!(
+        contract.query_closed(&app).unwrap(),
+        ClosedResp {
+            closed: false,
+            bought_out: false,
+            cancelled: false
+        }
+    );
+
+    // the 5 over the buy-now price is refunded right away
+    assert_eq!(
+        contract
+            .query_simulate_bid(&app, sender2.to_string(), 45)
+            .unwrap(),
+        SimulateBidResp::Accepted {
+            total: Uint128::new(40),
+            commission: Uint128::new(4),
+            retractable: Uint128::new(36),
+        }
+    );
+    contract.bid(&mut app, &sender2, &coins(45, ATOM)).unwrap();
+    assert_solvent(&app, &contract);
+    assert_eq!(
+        contract.query_closed(&app).unwrap(),
+        ClosedResp {
+            closed: true,
+            bought_out: true,
+            cancelled: false
+        }
+    );
+    assert_eq!(
+        contract.query_winner(&app).unwrap().winner,
+        Some(HighestResp {
+            address: sender2.clone(),
+            amount: Uint128::new(40)
+        })
+    );
+    assert_eq!(
+        app.wrap()
+            .query_balance(&sender2, ATOM)
+            .unwrap()
+            .amount
+            .u128(),
+        10
+    );
+    // 36 winning bid without commission + 6 total commission
+    assert_eq!(
+        app.wrap()
+            .query_balance(&owner, ATOM)
+            .unwrap()
+            .amount
+            .u128(),
+        42
+    );
+
+    let err = contract
+        .bid(&mut app, &sender1, &coins(30, ATOM))
+        .unwrap_err();
+    assert_eq!(err, ContractError::BiddingClosed {});
+
+    let err = contract.close(&mut app, &owner).unwrap_err();
+    assert_eq!(err, ContractError::BiddingClosed {});
+
+    contract.retract(&mut app, &sender1, None).unwrap();
+    assert_solvent(&app, &contract);
+    // 50 initial balance - 2 commission from 20 bid
+    assert_eq!(
+        app.wrap()
+            .query_balance(sender1, ATOM)
+            .unwrap()
+            .amount
+            .u128(),
+        48
+    );
+}
+
+#[test]
+fn buy_now_increment() {
+    let owner = Addr::unchecked("owner");
+    let sender1 = Addr::unchecked("sender1");
+    let sender2 = Addr::unchecked("sender2");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender1, coins(200, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &sender2, coins(200, ATOM))
+            .unwrap();
+    });
+    let code_id = BiddingContract::store_code(&mut app);
+
+    let contract = BiddingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Bidding contract",
+        None,
+        &InstantiateMsg {
+            commission: Some(Commission::flat(Decimal::zero())),
+            min_increment: Some(Uint128::new(10)),
+            buy_now_price: Some(Uint128::new(100)),
+            ..instantiate_msg()
+        },
+    )
+    .unwrap();
+
+    contract.bid(&mut app, &sender1, &coins(95, ATOM)).unwrap();
+
+    assert_eq!(
+        contract
+            .query_min_bid_to_lead(&app, sender2.to_string())
+            .unwrap(),
+        MinBidToLeadResp {
+            amount: Uint128::new(100),
+            commission: Uint128::zero(),
+            total: Uint128::new(100),
+        }
+    );
+    assert_eq!(
+        contract
+            .query_min_bid_to_lead(&app, sender1.to_string())
+            .unwrap(),
+        MinBidToLeadResp {
+            amount: Uint128::new(5),
+            commission: Uint128::zero(),
+            total: Uint128::new(100),
+        }
+    );
+
+    // 105 required by the increment is above the buy-now price, which still wins outright
+    contract.bid(&mut app, &sender2, &coins(200, ATOM)).unwrap();
+    assert_solvent(&app, &contract);
+    assert_eq!(
+        contract.query_winner(&app).unwrap().winner,
+        Some(HighestResp {
+            address: sender2.clone(),
+            amount: Uint128::new(100)
+        })
+    );
+    assert_eq!(
+        app.wrap()
+            .query_balance(&sender2, ATOM)
+            .unwrap()
+            .amount
+            .u128(),
+        100
+    );
+}
+
+#[test]
+fn settlement() {
+    let owner = Addr::unchecked("owner");
+    let beneficiary = Addr::unchecked("beneficiary");
+    let commission_recipient = Addr::unchecked("commission_recipient");
+    let sender1 = Addr::unchecked("sender1");
+    let sender2 = Addr::unchecked("sender2");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender1, coins(100, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &sender2, coins(100, ATOM))
+            .unwrap();
+    });
+    let code_id = BiddingContract::store_code(&mut app);
+
+    let contract = BiddingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Bidding contract",
+        None,
+        &InstantiateMsg {
+            beneficiary: Some(beneficiary.to_string()),
+            commission_recipients: Some(vec![CommissionShare {
+                address: commission_recipient.to_string(),
+                weight: Decimal::one(),
+            }]),
+            commission: Some(Commission::flat(Decimal::percent(10))),
+            ..instantiate_msg()
+        },
+    )
+    .unwrap();
+
+    // commission: 1 + 3 + 2
+    contract.bid(&mut app, &sender1, &coins(10, ATOM)).unwrap();
+    assert_solvent(&app, &contract);
+    contract.bid(&mut app, &sender2, &coins(30, ATOM)).unwrap();
+    assert_solvent(&app, &contract);
+    contract.bid(&mut app, &sender1, &coins(25, ATOM)).unwrap();
+    assert_solvent(&app, &contract);
+    contract.close(&mut app, &owner).unwrap();
+    assert_solvent(&app, &contract);
+    contract.retract(&mut app, &sender2, None).unwrap();
+    assert_solvent(&app, &contract);
+
+    let balance = |address: &Addr| {
+        app.wrap()
+            .query_balance(address, ATOM)
+            .unwrap()
+            .amount
+            .u128()
+    };
+    assert_eq!(balance(&owner), 0);
+    // 35 winning bid - 3 commission
+    assert_eq!(balance(&beneficiary), 32);
+    assert_eq!(balance(&commission_recipient), 6);
+    // 100 initial balance - 35 winning bid
+    assert_eq!(balance(&sender1), 65);
+    // 100 initial balance - 3 commission
+    assert_eq!(balance(&sender2), 97);
+    assert_eq!(balance(contract.addr()), 0);
+}
+
+#[test]
+fn commission_payout() {
+    let owner = Addr::unchecked("owner");
+    let commission_recipient = Addr::unchecked("commission_recipient");
+    let sender1 = Addr::unchecked("sender1");
+    let sender2 = Addr::unchecked("sender2");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender1, coins(100, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &sender2, coins(100, ATOM))
+            .unwrap();
+    });
+    let code_id = BiddingContract::store_code(&mut app);
+
+    let immediate = BiddingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Bidding contract",
+        None,
+        &InstantiateMsg {
+            commission_recipients: Some(vec![CommissionShare {
+                address: commission_recipient.to_string(),
+                weight: Decimal::one(),
+            }]),
+            commission: Some(Commission::flat(Decimal::percent(10))),
+            ..instantiate_msg()
+        },
+    )
+    .unwrap();
+    let deferred = BiddingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Bidding contract",
+        None,
+        &InstantiateMsg {
+            commission_recipients: Some(vec![CommissionShare {
+                address: commission_recipient.to_string(),
+                weight: Decimal::one(),
+            }]),
+            commission: Some(Commission::flat(Decimal::percent(10))),
+            commission_payout: Some(CommissionPayout::Deferred),
+            ..instantiate_msg()
+        },
+    )
+    .unwrap();
+
+    let balance = |app: &App, address: &Addr| {
+        app.wrap()
+            .query_balance(address, ATOM)
+            .unwrap()
+            .amount
+            .u128()
+    };
+
+    immediate.bid(&mut app, &sender1, &coins(10, ATOM)).unwrap();
+    assert_solvent(&app, &immediate);
+    assert_eq!(balance(&app, &commission_recipient), 1);
+    immediate.bid(&mut app, &sender2, &coins(20, ATOM)).unwrap();
+    assert_solvent(&app, &immediate);
+    assert_eq!(balance(&app, &commission_recipient), 3);
+    assert_eq!(balance(&app, immediate.addr()), 27);
+
+    deferred.bid(&mut app, &sender1, &coins(10, ATOM)).unwrap();
+    assert_solvent(&app, &deferred);
+    deferred.bid(&mut app, &sender2, &coins(20, ATOM)).unwrap();
+    assert_solvent(&app, &deferred);
+    assert_eq!(balance(&app, &commission_recipient), 3);
+    assert_eq!(balance(&app, deferred.addr()), 30);
+
+    // immediate commissions are not paid twice
+    immediate.close(&mut app, &owner).unwrap();
+    assert_solvent(&app, &immediate);
+    assert_eq!(balance(&app, &commission_recipient), 3);
+    assert_eq!(balance(&app, &owner), 18);
+
+    deferred.close(&mut app, &owner).unwrap();
+    assert_solvent(&app, &deferred);
+    assert_eq!(balance(&app, &commission_recipient), 6);
+    assert_eq!(balance(&app, &owner), 36);
+}
+
+#[test]
+fn commission_rate() {
+    let owner = Addr::unchecked("owner");
+    let sender1 = Addr::unchecked("sender1");
+    let sender2 = Addr::unchecked("sender2");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender1, coins(100, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &sender2, coins(100, ATOM))
+            .unwrap();
+    });
+    let code_id = BiddingContract::store_code(&mut app);
+
+    let err = BiddingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Bidding contract",
+        None,
+        &InstantiateMsg {
+            commission: Some(Commission::flat(Decimal::percent(250))),
+            ..instantiate_msg()
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidCommission {});
+
+    let contract = BiddingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Bidding contract",
+        None,
+        &InstantiateMsg {
+            commission: Some(Commission::flat(Decimal::percent(10))),
+            ..instantiate_msg()
+        },
+    )
+    .unwrap();
+
+    let balance = |app: &App, address: &Addr| {
+        app.wrap()
+            .query_balance(address, ATOM)
+            .unwrap()
+            .amount
+            .u128()
+    };
+
+    // 0.5 commission is rounded down and only tracked as dust
+    contract.bid(&mut app, &sender1, &coins(5, ATOM)).unwrap();
+    assert_eq!(balance(&app, &owner), 0);
+
+    // 0.6 commission is not topped up by someone else's dust
+    contract.bid(&mut app, &sender2, &coins(6, ATOM)).unwrap();
+    assert_eq!(balance(&app, &owner), 0);
+
+    contract.bid(&mut app, &sender1, &coins(3, ATOM)).unwrap();
+    assert_eq!(balance(&app, &owner), 0);
+    assert_eq!(
+        contract.query_config(&app).unwrap().commission_dust,
+        Decimal::percent(140)
+    );
+
+    contract.close(&mut app, &owner).unwrap();
+    contract.retract(&mut app, &sender2, None).unwrap();
+
+    // 8 winning bid without any commission
+    assert_eq!(balance(&app, &owner), 8);
+    assert_eq!(balance(&app, &sender2), 100);
+}
+
+#[test]
+fn commission_schedule() {
+    let owner = Addr::unchecked("owner");
+    let sender1 = Addr::unchecked("sender1");
+    let sender2 = Addr::unchecked("sender2");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender1, coins(1000, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &sender2, coins(1000, ATOM))
+            .unwrap();
+    });
+    let code_id = BiddingContract::store_code(&mut app);
+
+    let err = BiddingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Bidding contract",
+        None,
+        &InstantiateMsg {
+            commission: Some(Commission {
+                schedule: CommissionSchedule::Tiered {
+                    tiers: vec![CommissionTier {
+                        threshold: Uint128::new(100),
+                        rate: Decimal::percent(5),
+                    }],
+                },
+                min: None,
+                max: None,
+            }),
+            ..instantiate_msg()
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidCommission {});
+
+    let contract = BiddingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Bidding contract",
+        None,
+        &InstantiateMsg {
+            commission: Some(Commission {
+                schedule: CommissionSchedule::Tiered {
+                    tiers: vec![
+                        CommissionTier {
+                            threshold: Uint128::zero(),
+                            rate: Decimal::percent(10),
+                        },
+                        CommissionTier {
+                            threshold: Uint128::new(100),
+                            rate: Decimal::percent(5),
+                        },
+                    ],
+                },
+                min: Some(Uint128::new(2)),
+                max: Some(Uint128::new(20)),
+            }),
+            ..instantiate_msg()
+        },
+    )
+    .unwrap();
+
+    let preview = |app: &App, amount: u128| {
+        contract
+            .query_commission_preview(app, Uint128::new(amount))
+            .unwrap()
+            .commission
+            .u128()
+    };
+    // 10% clamped to the minimum
+    assert_eq!(preview(&app, 10), 2);
+    assert_eq!(preview(&app, 50), 5);
+    // 5% from the second tier
+    assert_eq!(preview(&app, 200), 10);
+    // 5% clamped to the maximum
+    assert_eq!(preview(&app, 1000), 20);
+    assert_eq!(
+        contract
+            .query_commission_preview(&app, Uint128::new(1))
+            .unwrap(),
+        CommissionPreviewResp {
+            commission: Uint128::new(2),
+            retractable: Uint128::zero(),
+        }
+    );
+
+    let err = contract
+        .bid(&mut app, &sender1, &coins(1, ATOM))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::BidBelowCommission {
+            commission: Uint128::new(2)
+        }
+    );
+
+    contract.bid(&mut app, &sender1, &coins(50, ATOM)).unwrap();
+    contract.bid(&mut app, &sender2, &coins(200, ATOM)).unwrap();
+    contract.bid(&mut app, &sender1, &coins(900, ATOM)).unwrap();
+
+    // 5 + 10 + 20 commission
+    assert_eq!(
+        app.wrap().query_balance(owner, ATOM).unwrap().amount.u128(),
+        35
+    );
+}
+
+#[test]
+fn commission_clamp() {
+    let owner = Addr::unchecked("owner");
+    let sender1 = Addr::unchecked("sender1");
+    let sender2 = Addr::unchecked("sender2");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender1, coins(100, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &sender2, coins(100, ATOM))
+            .unwrap();
+    });
+    let code_id = BiddingContract::store_code(&mut app);
+
+    let contract = BiddingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Bidding contract",
+        None,
+        &InstantiateMsg {
+            commission: Some(Commission {
+                schedule: CommissionSchedule::Flat {
+                    rate: Decimal::percent(10),
+                },
+                min: Some(Uint128::new(1)),
+                max: Some(Uint128::new(2)),
+            }),
+            ..instantiate_msg()
+        },
+    )
+    .unwrap();
+
+    let balance = |app: &App| {
+        app.wrap()
+            .query_balance(&owner, ATOM)
+            .unwrap()
+            .amount
+            .u128()
+    };
+    let dust = |app: &App| contract.query_config(app).unwrap().commission_dust;
+
+    // 0.5 commission clamped to the minimum leaves no dust behind
+    contract.bid(&mut app, &sender1, &coins(5, ATOM)).unwrap();
+    assert_eq!(balance(&app), 1);
+    assert_eq!(dust(&app), Decimal::zero());
+
+    // 1.5 commission is rounded down and kept as dust
+    contract.bid(&mut app, &sender2, &coins(15, ATOM)).unwrap();
+    assert_eq!(balance(&app), 2);
+    assert_eq!(dust(&app), Decimal::percent(50));
+
+    // 3.5 commission clamped to the maximum leaves no dust behind
+    contract.bid(&mut app, &sender1, &coins(35, ATOM)).unwrap();
+    assert_eq!(balance(&app), 4);
+    assert_eq!(dust(&app), Decimal::percent(50));
+
+    // 2.6 commission is rounded down, its dust only adds up in the tracked figure
+    contract.bid(&mut app, &sender2, &coins(26, ATOM)).unwrap();
+    assert_eq!(balance(&app), 6);
+    assert_eq!(dust(&app), Decimal::percent(110));
+}
+
+#[test]
+fn fixed_commission() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(100, ATOM))
+            .unwrap();
+    });
+    let code_id = BiddingContract::store_code(&mut app);
+
+    let contract = BiddingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Bidding contract",
+        None,
+        &InstantiateMsg {
+            commission: Some(Commission {
+                schedule: CommissionSchedule::Fixed {
+                    amount: Uint128::new(3),
+                },
+                min: None,
+                max: None,
+            }),
+            ..instantiate_msg()
+        },
+    )
+    .unwrap();
+
+    contract.bid(&mut app, &sender, &coins(10, ATOM)).unwrap();
+    contract.bid(&mut app, &sender, &coins(30, ATOM)).unwrap();
+
+    assert_eq!(
+        app.wrap().query_balance(owner, ATOM).unwrap().amount.u128(),
+        6
+    );
+}
+
+#[test]
+fn commission_split() {
+    let owner = Addr::unchecked("owner");
+    let treasury = Addr::unchecked("treasury");
+    let seller = Addr::unchecked("seller");
+    let partner = Addr::unchecked("partner");
+    let sender1 = Addr::unchecked("sender1");
+    let sender2 = Addr::unchecked("sender2");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender1, coins(100, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &sender2, coins(100, ATOM))
+            .unwrap();
+    });
+    let code_id = BiddingContract::store_code(&mut app);
+
+    let shares = |weights: [u64; 3]| {
+        Some(
+            [&treasury, &seller, &partner]
+                .iter()
+                .zip(weights)
+                .map(|(address, weight)| CommissionShare {
+                    address: address.to_string(),
+                    weight: Decimal::percent(weight),
+                })
+                .collect(),
+        )
+    };
+
+    let err = BiddingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Bidding contract",
+        None,
+        &InstantiateMsg {
+            commission_recipients: shares([50, 30, 30]),
+            ..instantiate_msg()
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidCommissionRecipients {});
+
+    let contract = BiddingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Bidding contract",
+        None,
+        &InstantiateMsg {
+            commission_recipients: shares([50, 30, 20]),
+            commission: Some(Commission::flat(Decimal::percent(10))),
+            commission_payout: Some(CommissionPayout::Deferred),
+            ..instantiate_msg()
+        },
+    )
+    .unwrap();
+
+    // 5 commission: 2 + 1 + 2 remainder
+    contract.bid(&mut app, &sender1, &coins(50, ATOM)).unwrap();
+    // 6 commission: 3 + 1 + 2 remainder
+    contract.bid(&mut app, &sender2, &coins(60, ATOM)).unwrap();
+
+    let earned = |amounts: [u128; 3]| CommissionEarnedResp {
+        recipients: [&treasury, &seller, &partner]
+            .iter()
+            .zip([50, 30, 20])
+            .zip(amounts)
+            .map(|((address, weight), earned)| CommissionEarned {
+                address: (*address).clone(),
+                weight: Decimal::percent(weight),
+                earned: Uint128::new(earned),
+            })
+            .collect(),
+    };
+    assert_eq!(
+        contract.query_commission_earned(&app).unwrap(),
+        earned([5, 2, 4])
+    );
+
+    contract.close(&mut app, &owner).unwrap();
+
+    let balance = |address: &Addr| {
+        app.wrap()
+            .query_balance(address, ATOM)
+            .unwrap()
+            .amount
+            .u128()
+    };
+    assert_eq!(balance(&treasury), 5);
+    assert_eq!(balance(&seller), 2);
+    assert_eq!(balance(&partner), 4);
+    // 60 winning bid - 6 commission
+    assert_eq!(balance(&owner), 54);
+}
+
+#[test]
+fn referral_rewards() {
+    let owner = Addr::unchecked("owner");
+    let referrer1 = Addr::unchecked("referrer1");
+    let referrer2 = Addr::unchecked("referrer2");
+    let sender1 = Addr::unchecked("sender1");
+    let sender2 = Addr::unchecked("sender2");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender1, coins(100, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &sender2, coins(100, ATOM))
+            .unwrap();
+    });
+    let code_id = BiddingContract::store_code(&mut app);
+
+    let contract = BiddingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Bidding contract",
+        None,
+        &InstantiateMsg {
+            commission: Some(Commission::flat(Decimal::percent(10))),
+            commission_payout: Some(CommissionPayout::Deferred),
+            referral_share: Some(Decimal::percent(50)),
+            ..instantiate_msg()
+        },
+    )
+    .unwrap();
+
+    let err = contract
+        .bid_with_referrer(&mut app, &sender1, &coins(20, ATOM), &sender1)
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidReferrer {});
+
+    // 2 commission: 1 to the referrer
+    contract
+        .bid_with_referrer(&mut app, &sender1, &coins(20, ATOM), &referrer1)
+        .unwrap();
+    assert_solvent(&app, &contract);
+    // 4 commission: 2 to the referrer
+    contract
+        .bid_with_referrer(&mut app, &sender2, &coins(40, ATOM), &referrer2)
+        .unwrap();
+    assert_solvent(&app, &contract);
+    // 3 commission: 1 to the referrer
+    contract
+        .bid_with_referrer(&mut app, &sender1, &coins(30, ATOM), &referrer2)
+        .unwrap();
+    assert_solvent(&app, &contract);
+
+    assert_eq!(
+        contract.query_referral_earnings(&app, None, None).unwrap(),
+        ReferralEarningsResp {
+            referrals: vec![
+                ReferralEarnings {
+                    address: referrer1.clone(),
+                    earned: Uint128::new(1),
+                    claimed: Uint128::zero(),
+                },
+                ReferralEarnings {
+                    address: referrer2.clone(),
+                    earned: Uint128::new(3),
+                    claimed: Uint128::zero(),
+                },
+            ]
+        }
+    );
+    assert_eq!(
+        contract
+            .query_referral_earnings(&app, Some(referrer1.to_string()), Some(1))
+            .unwrap()
+            .referrals
+            .len(),
+        1
+    );
+
+    // deferred rewards are released on close
+    let err = contract.claim_referral(&mut app, &referrer2).unwrap_err();
+    assert_eq!(err, ContractError::BiddingNotClosed {});
+
+    contract.close(&mut app, &owner).unwrap();
+    assert_solvent(&app, &contract);
+    contract.claim_referral(&mut app, &referrer2).unwrap();
+    assert_solvent(&app, &contract);
+
+    let err = contract.claim_referral(&mut app, &referrer2).unwrap_err();
+    assert_eq!(err, ContractError::NoReferralRewards {});
+
+    let balance = |address: &Addr| {
+        app.wrap()
+            .query_balance(address, ATOM)
+            .unwrap()
+            .amount
+            .u128()
+    };
+    assert_eq!(balance(&referrer2), 3);
+    assert_eq!(balance(&referrer1), 0);
+    // 45 winning bid without commission + 5 commission
+    assert_eq!(balance(&owner), 50);
+    // referrer1 reward is still waiting to be claimed
+    assert_eq!(balance(contract.addr()), 37);
+}
+
+#[test]
+fn migrate() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(100, ATOM))
+            .unwrap();
+    });
+    let code_id = BiddingContract::store_code(&mut app);
+    let new_code_id = BiddingContract::store_code(&mut app);
+
+    let contract = BiddingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Bidding contract",
+        &owner,
+        &instantiate_msg(),
+    )
+    .unwrap();
+    contract.bid(&mut app, &sender, &coins(20, ATOM)).unwrap();
+    let config = contract.query_config(&app).unwrap();
+
+    contract.migrate(&mut app, &owner, new_code_id).unwrap();
+
+    assert_eq!(contract.query_config(&app).unwrap(), config);
+    assert_eq!(
+        contract.query_bid(&app, sender.to_string()).unwrap(),
+        BidResp {
+            bid: Uint128::new(20)
+        }
+    );
+    assert_eq!(
+        cw2::query_contract_info(&app.wrap(), contract.addr())
+            .unwrap()
+            .version,
+        env!("CARGO_PKG_VERSION")
+    );
+}
+
+#[test]
+fn migrate_legacy_state() {
+    let owner = Addr::unchecked("owner");
+    let sender1 = Addr::unchecked("sender1");
+    let sender2 = Addr::unchecked("sender2");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender1, coins(100, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &sender2, coins(100, ATOM))
+            .unwrap();
+    });
+    let code_id = BiddingContract::store_code(&mut app);
+
+    // 20 bid with 2 commission placed on the 0.1.0 release
+    let contract = instantiate_legacy(
+        &mut app,
+        &sender1,
+        &owner,
+        env!("CARGO_PKG_NAME"),
+        "0.1.0",
+        &coins(20, ATOM),
+    );
+    contract.migrate(&mut app, &owner, code_id).unwrap();
+
+    let config = contract.query_config(&app).unwrap();
+    assert_eq!(
+        config.asset,
+        AssetInfo::Native {
+            denom: ATOM.to_owned()
+        }
+    );
+    assert_eq!(config.commission, Commission::flat(Decimal::percent(10)));
+    assert_eq!(config.commission_payout, CommissionPayout::Deferred);
+    assert_eq!(
+        contract.query_highest_bid(&app).unwrap(),
+        Some(HighestResp {
+            address: sender1.clone(),
+            amount: Uint128::new(20)
+        })
+    );
+    assert_eq!(
+        contract.query_top_bids(&app, None).unwrap(),
+        TopBidsResp {
+            bids: vec![BidEntry {
+                address: sender1.clone(),
+                retractable: Uint128::new(18),
+                commission: Uint128::new(2),
+                total: Uint128::new(20),
+            }]
+        }
+    );
+
+    // 3 commission
+    contract.bid(&mut app, &sender2, &coins(30, ATOM)).unwrap();
+    assert_solvent(&app, &contract);
+    contract.close(&mut app, &owner).unwrap();
+    assert_solvent(&app, &contract);
+    contract.retract(&mut app, &sender1, None).unwrap();
+    assert_solvent(&app, &contract);
+
+    let balance = |address: &Addr| {
+        app.wrap()
+            .query_balance(address, ATOM)
+            .unwrap()
+            .amount
+            .u128()
+    };
+    // 27 winning bid without commission + 5 total commission
+    assert_eq!(balance(&owner), 32);
+    // 100 initial balance - 2 commission
+    assert_eq!(balance(&sender1), 98);
+    assert_eq!(balance(contract.addr()), 0);
+}
+
+#[test]
+fn migrate_v2_state() {
+    migrate_released_state("0.2.0");
+}
+
+#[test]
+fn migrate_v3_state() {
+    migrate_released_state("0.3.0");
+}
+
+fn migrate_released_state(version: &str) {
+    let owner = Addr::unchecked("owner");
+    let sender1 = Addr::unchecked("sender1");
+    let sender2 = Addr::unchecked("sender2");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender1, coins(100, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &sender2, coins(100, ATOM))
+            .unwrap();
+    });
+    let code_id = BiddingContract::store_code(&mut app);
+
+    // 20 bid with 2 commission placed on an earlier release
+    let contract = instantiate_legacy(
+        &mut app,
+        &sender1,
+        &owner,
+        env!("CARGO_PKG_NAME"),
+        version,
+        &coins(20, ATOM),
+    );
+    contract.migrate(&mut app, &owner, code_id).unwrap();
+    assert_solvent(&app, &contract);
+
+    assert_eq!(
+        contract.query_ownership(&app).unwrap().owner,
+        Some(owner.clone())
+    );
+    assert_eq!(
+        contract.query_paused(&app).unwrap(),
+        PausedResp { paused: false }
+    );
+    assert_eq!(
+        contract.query_top_bids(&app, None).unwrap(),
+        TopBidsResp {
+            bids: vec![BidEntry {
+                address: sender1.clone(),
+                retractable: Uint128::new(18),
+                commission: Uint128::new(2),
+                total: Uint128::new(20),
+            }]
+        }
+    );
+
+    // 3 commission
+    contract.bid(&mut app, &sender2, &coins(30, ATOM)).unwrap();
+    assert_solvent(&app, &contract);
+    contract.close(&mut app, &owner).unwrap();
+    assert_solvent(&app, &contract);
+    contract.retract(&mut app, &sender1, None).unwrap();
+    assert_solvent(&app, &contract);
+
+    // 27 winning bid without commission + 5 total commission
+    assert_eq!(
+        app.wrap().query_balance(&owner, ATOM).unwrap(),
+        coin(32, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_balance(&sender1, ATOM).unwrap(),
+        coin(98, ATOM)
+    );
+}
+
+#[test]
+fn migrate_rejected() {
+    let owner = Addr::unchecked("owner");
+    let mut app = App::default();
+    let code_id = BiddingContract::store_code(&mut app);
+
+    let contract = instantiate_legacy(
+        &mut app,
+        &owner,
+        &owner,
+        "crates.io:other-contract",
+        "0.1.0",
+        &[],
+    );
+    let err = contract.migrate(&mut app, &owner, code_id).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidContract {
+            contract: "crates.io:other-contract".to_owned()
+        }
+    );
+
+    let contract = instantiate_legacy(
+        &mut app,
+        &owner,
+        &owner,
+        env!("CARGO_PKG_NAME"),
+        "99.0.0",
+        &[],
+    );
+    let err = contract.migrate(&mut app, &owner, code_id).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Downgrade {
+            stored: "99.0.0".to_owned(),
+            current: env!("CARGO_PKG_VERSION").to_owned()
+        }
+    );
+}
+
+#[test]
+fn ownership_transfer() {
+    let owner = Addr::unchecked("owner");
+    let new_owner = Addr::unchecked("new_owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &new_owner, coins(10, ATOM))
+            .unwrap();
+    });
+    let code_id = BiddingContract::store_code(&mut app);
+    let now = app.block_info().time;
+
+    let contract = BiddingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Bidding contract",
+        None,
+        &instantiate_msg(),
+    )
+    .unwrap();
+
+    let err = contract
+        .propose_owner(&mut app, &sender, &sender, None)
+        .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let err = contract.accept_ownership(&mut app, &new_owner).unwrap_err();
+    assert_eq!(err, ContractError::NoPendingOwner {});
+
+    contract
+        .propose_owner(&mut app, &owner, &new_owner, Some(now.plus_seconds(100)))
+        .unwrap();
+    assert_eq!(
+        contract.query_ownership(&app).unwrap(),
+        OwnershipResp {
+            owner: Some(owner.clone()),
+            pending_owner: Some(new_owner.clone()),
+            pending_expiry: Some(now.plus_seconds(100)),
+        }
+    );
+
+    let err = contract.accept_ownership(&mut app, &sender).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    app.update_block(|block| block.time = block.time.plus_seconds(100));
+    let err = contract.accept_ownership(&mut app, &new_owner).unwrap_err();
+    assert_eq!(err, ContractError::OwnershipProposalExpired {});
+
+    contract
+        .propose_owner(&mut app, &owner, &new_owner, None)
+        .unwrap();
+    contract.accept_ownership(&mut app, &new_owner).unwrap();
+    assert_eq!(
+        contract.query_ownership(&app).unwrap(),
+        OwnershipResp {
+            owner: Some(new_owner.clone()),
+            pending_owner: None,
+            pending_expiry: None,
+        }
+    );
+
+    // the new owner cannot bid, the previous one cannot close anymore
+    let err = contract
+        .bid(&mut app, &new_owner, &coins(10, ATOM))
+        .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let err = contract.close(&mut app, &owner).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // without an end time the owner is the only one who can close
+    let err = contract
+        .renounce_ownership(&mut app, &new_owner)
+        .unwrap_err();
+    assert_eq!(err, ContractError::OpenEndedBidding {});
+
+    contract.close(&mut app, &new_owner).unwrap();
+    contract.renounce_ownership(&mut app, &new_owner).unwrap();
+    assert_eq!(contract.query_ownership(&app).unwrap().owner, None);
+}
+
+#[test]
+fn renounce_ownership() {
+    let owner = Addr::unchecked("owner");
+    let sender1 = Addr::unchecked("sender1");
+    let sender2 = Addr::unchecked("sender2");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender1, coins(100, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &sender2, coins(100, ATOM))
+            .unwrap();
+    });
+    let code_id = BiddingContract::store_code(&mut app);
+    let now = app.block_info().time;
+
+    let contract = BiddingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Bidding contract",
+        None,
+        &InstantiateMsg {
+            end_time: Some(now.plus_seconds(100)),
+            ..instantiate_msg()
+        },
+    )
+    .unwrap();
+
+    contract.bid(&mut app, &sender1, &coins(20, ATOM)).unwrap();
+    contract.bid(&mut app, &sender2, &coins(40, ATOM)).unwrap();
+    contract.renounce_ownership(&mut app, &owner).unwrap();
+
+    // once the end time passes the bidders close and retract on their own
+    app.update_block(|block| block.time = block.time.plus_seconds(100));
+    contract.close(&mut app, &sender1).unwrap();
+    contract.retract(&mut app, &sender1, None).unwrap();
+
+    // 100 initial balance - 1 commission from 20 bid
+    assert_eq!(
+        app.wrap().query_balance(&sender1, ATOM).unwrap(),
+        coin(99, ATOM)
+    );
+}
+
+#[test]
+fn cancel() {
+    let owner = Addr::unchecked("owner");
+    let referrer = Addr::unchecked("referrer");
+    let sender1 = Addr::unchecked("sender1");
+    let sender2 = Addr::unchecked("sender2");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender1, coins(100, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &sender2, coins(100, ATOM))
+            .unwrap();
+    });
+    let code_id = BiddingContract::store_code(&mut app);
+
+    let immediate = BiddingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Bidding contract",
+        None,
+        &InstantiateMsg {
+            commission: Some(Commission::flat(Decimal::percent(10))),
+            ..instantiate_msg()
+        },
+    )
+    .unwrap();
+    let deferred = BiddingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Bidding contract",
+        None,
+        &InstantiateMsg {
+            commission: Some(Commission::flat(Decimal::percent(10))),
+            commission_payout: Some(CommissionPayout::Deferred),
+            referral_share: Some(Decimal::percent(50)),
+            ..instantiate_msg()
+        },
+    )
+    .unwrap();
+
+    let balance = |app: &App, address: &Addr| {
+        app.wrap()
+            .query_balance(address, ATOM)
+            .unwrap()
+            .amount
+            .u128()
+    };
+
+    // 1 commission
+    immediate.bid(&mut app, &sender1, &coins(10, ATOM)).unwrap();
+    assert_solvent(&app, &immediate);
+    let err = immediate.cancel(&mut app, &sender1, None, &[]).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    // the commission already paid out has to be sent back to refund it
+    let err = immediate
+        .cancel(&mut app, &owner, Some(true), &[])
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidRefundFunds {
+            required: Uint128::new(1)
+        }
+    );
+
+    immediate.cancel(&mut app, &owner, None, &[]).unwrap();
+    assert_solvent(&app, &immediate);
+    assert_eq!(
+        immediate.query_closed(&app).unwrap(),
+        ClosedResp {
+            closed: true,
+            bought_out: false,
+            cancelled: true
+        }
+    );
+    assert_eq!(
+        immediate.query_status(&app).unwrap().status,
+        AuctionStatus::Cancelled
+    );
+    assert_eq!(immediate.query_winner(&app).unwrap().winner, None);
+    let err = immediate.close(&mut app, &owner).unwrap_err();
+    assert_eq!(err, ContractError::BiddingClosed {});
+
+    // the highest bidder retracts without the commission
+    immediate.retract(&mut app, &sender1, None).unwrap();
+    assert_solvent(&app, &immediate);
+    assert_eq!(balance(&app, &sender1), 99);
+    assert_eq!(balance(&app, &owner), 1);
+
+    let refunded = BiddingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Bidding contract",
+        None,
+        &InstantiateMsg {
+            commission: Some(Commission::flat(Decimal::percent(10))),
+            ..instantiate_msg()
+        },
+    )
+    .unwrap();
+
+    // 1 commission paid out right away, then sent back by the owner
+    refunded.bid(&mut app, &sender2, &coins(10, ATOM)).unwrap();
+    assert_eq!(balance(&app, &owner), 2);
+    let err = refunded
+        .cancel(&mut app, &owner, Some(true), &coins(2, ATOM))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidRefundFunds {
+            required: Uint128::new(1)
+        }
+    );
+    refunded
+        .cancel(&mut app, &owner, Some(true), &coins(1, ATOM))
+        .unwrap();
+    assert_solvent(&app, &refunded);
+
+    refunded.retract(&mut app, &sender2, None).unwrap();
+    assert_solvent(&app, &refunded);
+    assert_eq!(balance(&app, &sender2), 100);
+    assert_eq!(balance(&app, &owner), 1);
+    assert_eq!(balance(&app, refunded.addr()), 0);
+
+    // 2 and 4 commission, half of it to the referrer
+    deferred
+        .bid_with_referrer(&mut app, &sender1, &coins(20, ATOM), &referrer)
+        .unwrap();
+    assert_solvent(&app, &deferred);
+    deferred
+        .bid_with_referrer(&mut app, &sender2, &coins(40, ATOM), &referrer)
+        .unwrap();
+    assert_solvent(&app, &deferred);
+    deferred.cancel(&mut app, &owner, Some(true), &[]).unwrap();
+    assert_solvent(&app, &deferred);
+
+    let err = deferred.claim_referral(&mut app, &referrer).unwrap_err();
+    assert_eq!(err, ContractError::NoReferralRewards {});
+
+    // everyone gets the full bid back
+    deferred.retract(&mut app, &sender1, None).unwrap();
+    assert_solvent(&app, &deferred);
+    deferred.retract(&mut app, &sender2, None).unwrap();
+    assert_solvent(&app, &deferred);
+    assert_eq!(balance(&app, &sender1), 99);
+    assert_eq!(balance(&app, &sender2), 100);
+    assert_eq!(balance(&app, &owner), 1);
+    assert_eq!(balance(&app, deferred.addr()), 0);
+}
+
+#[test]
+fn cancel_cw20() {
+    let owner = Addr::unchecked("owner");
+    let sender = Addr::unchecked("sender");
+
+    let mut app = App::default();
+    let token = instantiate_cw20(&mut app, &owner, &[(&sender, 20)]);
+    let code_id = BiddingContract::store_code(&mut app);
+
+    let contract = BiddingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Bidding contract",
+        None,
+        &InstantiateMsg {
+            asset: AssetInfo::Cw20 {
+                address: token.to_string(),
+            },
+            commission: Some(Commission::flat(Decimal::percent(10))),
+            ..instantiate_msg()
+        },
+    )
+    .unwrap();
+
+    // 1 commission is paid out to the owner
+    contract.bid_cw20(&mut app, &sender, &token, 10).unwrap();
+    let err = contract
+        .cancel(&mut app, &owner, Some(true), &[])
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::InvalidRefundFunds {
+            required: Uint128::new(1)
+        }
+    );
+    let err = contract
+        .cancel_cw20(&mut app, &sender, &token, 1)
+        .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    contract.cancel_cw20(&mut app, &owner, &token, 1).unwrap();
+    assert_solvent(&app, &contract);
+    contract.retract(&mut app, &sender, None).unwrap();
+    assert_solvent(&app, &contract);
+
+    assert_eq!(cw20_balance(&app, &token, &sender), 20);
+    assert_eq!(cw20_balance(&app, &token, &owner), 0);
+    assert_eq!(cw20_balance(&app, &token, contract.addr()), 0);
+}
+
+#[test]
+fn pause() {
+    let owner = Addr::unchecked("owner");
+    let guardian = Addr::unchecked("guardian");
+    let sender = Addr::unchecked("sender");
+    let sender2 = Addr::unchecked("sender2");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender, coins(100, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &sender2, coins(100, ATOM))
+            .unwrap();
+    });
+    let code_id = BiddingContract::store_code(&mut app);
+
+    let contract = BiddingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Bidding contract",
+        None,
+        &InstantiateMsg {
+            guardian: Some(guardian.to_string()),
+            ..instantiate_msg()
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        contract.query_config(&app).unwrap().guardian,
+        Some(guardian.clone())
+    );
+
+    let err = contract.pause(&mut app, &sender).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    contract.pause(&mut app, &guardian).unwrap();
+    assert_eq!(
+        contract.query_paused(&app).unwrap(),
+        PausedResp { paused: true }
+    );
+
+    let err = contract
+        .bid(&mut app, &sender, &coins(10, ATOM))
+        .unwrap_err();
+    assert_eq!(err, ContractError::Paused {});
+
+    contract.unpause(&mut app, &owner).unwrap();
+    assert_eq!(
+        contract.query_paused(&app).unwrap(),
+        PausedResp { paused: false }
+    );
+
+    contract.bid(&mut app, &sender, &coins(10, ATOM)).unwrap();
+    contract.bid(&mut app, &sender2, &coins(20, ATOM)).unwrap();
+
+    // retracting from a closed bidding keeps working while paused
+    contract.close(&mut app, &owner).unwrap();
+    contract.pause(&mut app, &owner).unwrap();
+    contract.retract(&mut app, &sender, None).unwrap();
+
+    assert_eq!(
+        app.wrap().query_balance(&sender, ATOM).unwrap(),
+        coin(100, ATOM)
+    );
+}
+
+#[test]
+fn accounting() {
+    let owner = Addr::unchecked("owner");
+    let referrer = Addr::unchecked("referrer");
+    let sender1 = Addr::unchecked("sender1");
+    let sender2 = Addr::unchecked("sender2");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender1, coins(100, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &sender2, coins(100, ATOM))
+            .unwrap();
+    });
+    let code_id = BiddingContract::store_code(&mut app);
+
+    let contract = BiddingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Bidding contract",
+        None,
+        &InstantiateMsg {
+            commission: Some(Commission::flat(Decimal::percent(10))),
+            commission_payout: Some(CommissionPayout::Deferred),
+            referral_share: Some(Decimal::percent(50)),
+            ..instantiate_msg()
+        },
+    )
+    .unwrap();
+
+    // 2 and 4 commission, half of the latter to the referrer
+    contract.bid(&mut app, &sender1, &coins(20, ATOM)).unwrap();
+    contract
+        .bid_with_referrer(&mut app, &sender2, &coins(40, ATOM), &referrer)
+        .unwrap();
+    assert_eq!(
+        contract.query_accounting(&app).unwrap(),
+        AccountingResp {
+            balance: Uint128::new(60),
+            total_retractable: Uint128::new(54),
+            commission_held: Uint128::new(6),
+            liabilities: Uint128::new(60),
+            solvent: true,
+        }
+    );
+
+    // 36 goes to the owner along with 4 commission, the referrer reward stays until claimed
+    contract.close(&mut app, &owner).unwrap();
+    assert_eq!(
+        contract.query_accounting(&app).unwrap(),
+        AccountingResp {
+            balance: Uint128::new(20),
+            total_retractable: Uint128::new(18),
+            commission_held: Uint128::new(2),
+            liabilities: Uint128::new(20),
+            solvent: true,
+        }
+    );
+
+    contract.retract(&mut app, &sender1, None).unwrap();
+    contract.claim_referral(&mut app, &referrer).unwrap();
+    assert_eq!(
+        contract.query_accounting(&app).unwrap(),
+        AccountingResp {
+            balance: Uint128::zero(),
+            total_retractable: Uint128::zero(),
+            commission_held: Uint128::zero(),
+            liabilities: Uint128::zero(),
+            solvent: true,
+        }
+    );
+}
+
+#[test]
+fn stray_funds() {
+    let owner = Addr::unchecked("owner");
+    let sender1 = Addr::unchecked("sender1");
+    let sender2 = Addr::unchecked("sender2");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender1, vec![coin(100, ATOM), coin(100, "ujuno")])
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &sender2, coins(100, ATOM))
+            .unwrap();
+    });
+    let code_id = BiddingContract::store_code(&mut app);
+
+    let contract = BiddingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Bidding contract",
+        None,
+        &instantiate_msg(),
+    )
+    .unwrap();
+
+    let err = contract.bid(&mut app, &sender1, &[]).unwrap_err();
+    assert_eq!(err, ContractError::Payment(PaymentError::NoFunds {}));
+
+    let err = contract
+        .bid(&mut app, &sender1, &[coin(10, ATOM), coin(10, "ujuno")])
+        .unwrap_err();
+    assert_eq!(err, ContractError::Payment(PaymentError::MultipleDenoms {}));
+
+    let err = contract
+        .bid(&mut app, &sender1, &coins(10, "ujuno"))
+        .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Payment(PaymentError::MissingDenom(ATOM.to_owned()))
+    );
+
+    let err: ContractError = app
+        .execute_contract(
+            sender1.clone(),
+            contract.addr().clone(),
+            &ExecMsg::Close {},
+            &coins(10, "ujuno"),
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Payment(PaymentError::NonPayable {}));
+
+    contract.bid(&mut app, &sender1, &coins(10, ATOM)).unwrap();
+    contract.bid(&mut app, &sender2, &coins(20, ATOM)).unwrap();
+
+    // funds sent around the bidding are not owed to anyone
+    app.send_tokens(sender1.clone(), contract.addr().clone(), &coins(5, ATOM))
+        .unwrap();
+    app.send_tokens(sender1.clone(), contract.addr().clone(), &coins(7, "ujuno"))
+        .unwrap();
+
+    let err = contract.sweep(&mut app, &owner, ATOM).unwrap_err();
+    assert_eq!(err, ContractError::BiddingNotClosed {});
+
+    contract.close(&mut app, &owner).unwrap();
+
+    let err = contract.sweep(&mut app, &sender1, ATOM).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // 19 winning bid + 1 commission + 5 swept
+    contract.sweep(&mut app, &owner, ATOM).unwrap();
+    contract.sweep(&mut app, &owner, "ujuno").unwrap();
+    assert_eq!(
+        app.wrap().query_all_balances(&owner).unwrap(),
+        vec![coin(25, ATOM), coin(7, "ujuno")]
+    );
+
+    let err = contract.sweep(&mut app, &owner, ATOM).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::NothingToSweep {
+            denom: ATOM.to_owned()
+        }
+    );
+
+    // the losing bid is still there to be retracted
+    contract.retract(&mut app, &sender1, None).unwrap();
+    assert_eq!(
+        app.wrap().query_balance(contract.addr(), ATOM).unwrap(),
+        coin(0, ATOM)
+    );
+}
+
+#[test]
+fn retract_with_hook() {
+    let owner = Addr::unchecked("owner");
+    let sender1 = Addr::unchecked("sender1");
+    let sender2 = Addr::unchecked("sender2");
+
+    let mut app = App::new(|router, _api, storage| {
+        router
+            .bank
+            .init_balance(storage, &sender1, coins(100, ATOM))
+            .unwrap();
+        router
+            .bank
+            .init_balance(storage, &sender2, coins(100, ATOM))
+            .unwrap();
+    });
+    let code_id = BiddingContract::store_code(&mut app);
+    let vault_code_id = app.store_code(vault_contract());
+
+    let contract = BiddingContract::instantiate(
+        &mut app,
+        code_id,
+        &owner,
+        "Bidding contract",
+        None,
+        &InstantiateMsg {
+            commission: Some(Commission::flat(Decimal::percent(10))),
+            ..instantiate_msg()
+        },
+    )
+    .unwrap();
+    let vault = app
+        .instantiate_contract(vault_code_id, owner.clone(), &Empty {}, &[], "Vault", None)
+        .unwrap();
+
+    contract.bid(&mut app, &sender1, &coins(10, ATOM)).unwrap();
+    contract.bid(&mut app, &sender2, &coins(20, ATOM)).unwrap();
+    contract.close(&mut app, &owner).unwrap();
+
+    // a typo'd receiver is rejected instead of paying the sender
+    let err = contract
+        .retract(&mut app, &sender1, Some("x".to_owned()))
+        .unwrap_err();
+    assert!(matches!(err, ContractError::Std(_)));
+    assert_eq!(
+        contract.query_bid(&app, sender1.to_string()).unwrap(),
+        BidResp {
+            bid: Uint128::new(10)
+        }
+    );
+
+    let msg = to_binary(&VaultExecMsg::Deposit {
+        beneficiary: sender1.to_string(),
+    })
+    .unwrap();
+    contract
+        .retract_with_hook(&mut app, &sender1, &vault, msg)
+        .unwrap();
+    assert_solvent(&app, &contract);
+
+    // 10 bid - 1 commission
+    let deposited: Uint128 = app
+        .wrap()
+        .query_wasm_smart(
+            &vault,
+            &VaultQueryMsg::Deposited {
+                beneficiary: sender1.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(deposited, Uint128::new(9));
+    assert_eq!(
+        app.wrap().query_balance(&vault, ATOM).unwrap(),
+        coin(9, ATOM)
+    );
+    assert_eq!(
+        app.wrap().query_balance(&sender1, ATOM).unwrap(),
+        coin(90, ATOM)
+    );
+}
@@ -1,23 +1,427 @@
-use cosmwasm_std::{Addr, Uint128};
-use cw_storage_plus::{Item, Map};
+use crate::msg::{AntiSniping, AssetInfo, CommissionPayout};
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    coins, to_binary, Addr, BankMsg, Binary, CosmosMsg, Decimal, QuerierWrapper, StdResult,
+    Timestamp, Uint128, Uint256, WasmMsg,
+};
+use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg};
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct State {
+    pub closed: bool,
+    // none once the ownership is renounced
+    pub owner: Option<Addr>,
+    // may pause and unpause bidding alongside the owner
+    pub guardian: Option<Addr>,
+    pub paused: bool,
+    pub beneficiary: Addr,
+    pub commission_recipients: Vec<CommissionRecipient>,
+    pub asset: Asset,
+    pub commission_total: Uint128,
+    pub commission: Commission,
+    // fractions of a unit left over from rounding commissions down
+    pub commission_dust: Decimal,
+    // part of every commission credited to the bid referrer
+    pub referral_share: Decimal,
+    pub commission_payout: CommissionPayout,
+    pub max_bid: Option<(Addr, Uint128)>,
+    // sum of every retractable bid still held by the contract
+    pub total_retractable: Uint128,
+    pub start_time: Option<Timestamp>,
+    pub end_time: Option<Timestamp>,
+    pub anti_sniping: Option<AntiSniping>,
+    pub reserve_price: Option<Uint128>,
+    pub min_opening_bid: Option<Uint128>,
+    pub min_increment: Option<Uint128>,
+    pub min_increment_rate: Option<Decimal>,
+    pub buy_now_price: Option<Uint128>,
+    pub bought_out: bool,
+    pub cancelled: bool,
+    pub commissions_refunded: bool,
+}
+pub const STATE: Item<State> = Item::new("state");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PendingOwner {
+    pub address: Addr,
+    pub expiry: Option<Timestamp>,
+}
+pub const PENDING_OWNER: Item<PendingOwner> = Item::new("pending_owner");
+
+// state layout of the 0.1.0 release, stored under the same key
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LegacyState {
     pub closed: bool,
     pub owner: Addr,
     pub commission_total: Uint128,
     pub commission_percent: u8,
     pub max_bid: Option<(Addr, Uint128)>,
 }
-pub const STATE: Item<State> = Item::new("state");
+pub const LEGACY_STATE: Item<LegacyState> = Item::new("state");
+
+// state layout of the 0.2.0 release, before ownership could change hands or bidding be stopped
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct StateV2 {
+    pub closed: bool,
+    pub owner: Addr,
+    pub beneficiary: Addr,
+    pub commission_recipients: Vec<CommissionRecipient>,
+    pub asset: Asset,
+    pub commission_total: Uint128,
+    pub commission: Commission,
+    pub commission_dust: Decimal,
+    pub referral_share: Decimal,
+    pub commission_payout: CommissionPayout,
+    pub max_bid: Option<(Addr, Uint128)>,
+    pub start_time: Option<Timestamp>,
+    pub end_time: Option<Timestamp>,
+    pub anti_sniping: Option<AntiSniping>,
+    pub reserve_price: Option<Uint128>,
+    pub min_opening_bid: Option<Uint128>,
+    pub min_increment: Option<Uint128>,
+    pub min_increment_rate: Option<Decimal>,
+    pub buy_now_price: Option<Uint128>,
+    pub bought_out: bool,
+}
+pub const STATE_V2: Item<StateV2> = Item::new("state");
+
+// state layout of the 0.3.0 release, before the retractable total was tracked
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct StateV3 {
+    pub closed: bool,
+    pub owner: Option<Addr>,
+    pub guardian: Option<Addr>,
+    pub paused: bool,
+    pub beneficiary: Addr,
+    pub commission_recipients: Vec<CommissionRecipient>,
+    pub asset: Asset,
+    pub commission_total: Uint128,
+    pub commission: Commission,
+    pub commission_dust: Decimal,
+    pub referral_share: Decimal,
+    pub commission_payout: CommissionPayout,
+    pub max_bid: Option<(Addr, Uint128)>,
+    pub start_time: Option<Timestamp>,
+    pub end_time: Option<Timestamp>,
+    pub anti_sniping: Option<AntiSniping>,
+    pub reserve_price: Option<Uint128>,
+    pub min_opening_bid: Option<Uint128>,
+    pub min_increment: Option<Uint128>,
+    pub min_increment_rate: Option<Decimal>,
+    pub buy_now_price: Option<Uint128>,
+    pub bought_out: bool,
+    pub cancelled: bool,
+    pub commissions_refunded: bool,
+}
+pub const STATE_V3: Item<StateV3> = Item::new("state");
+
+impl State {
+    pub fn is_owner(&self, address: &Addr) -> bool {
+        self.owner.as_ref() == Some(address)
+    }
+
+    pub fn is_guardian(&self, address: &Addr) -> bool {
+        self.guardian.as_ref() == Some(address)
+    }
+
+    // shares of the commission by recipient weight, the last one receiving the rounding remainder
+    pub fn split_commission(&self, commission: Uint128) -> Vec<(Addr, Uint128)> {
+        let mut remaining = commission;
+        let mut shares = vec![];
+
+        for (i, recipient) in self.commission_recipients.iter().enumerate() {
+            let share = if i + 1 == self.commission_recipients.len() {
+                remaining
+            } else {
+                commission * recipient.weight
+            };
+            remaining -= share;
+            shares.push((recipient.address.clone(), share));
+        }
+
+        shares
+    }
+
+    pub fn reserve_met(&self) -> bool {
+        match (self.reserve_price, &self.max_bid) {
+            (None, _) => true,
+            (Some(reserve_price), Some(max_bid)) => max_bid.1 >= reserve_price,
+            (Some(_), None) => false,
+        }
+    }
+
+    // smallest total bid that can take the lead
+    pub fn min_bid(&self) -> StdResult<Uint128> {
+        match self.max_bid {
+            Some((_, max_bid)) => {
+                let rate = self.min_increment_rate.unwrap_or_default();
+                let precision = Uint256::from(10u128.pow(Decimal::DECIMAL_PLACES));
+                let increment = Uint128::try_from(max_bid.full_mul(rate.atomics()) / precision)?;
+
+                let absolute = max_bid.checked_add(self.min_increment.unwrap_or_default())?;
+                let relative = max_bid.checked_add(increment)?;
+                Ok(max_bid
+                    .checked_add(Uint128::one())?
+                    .max(absolute)
+                    .max(relative))
+            }
+            None => Ok(self.min_opening_bid.unwrap_or_default()),
+        }
+    }
+}
+
+#[cw_serde]
+pub struct CommissionRecipient {
+    pub address: Addr,
+    pub weight: Decimal,
+}
+
+// commission accrued per recipient
+pub const COMMISSION_EARNED: Map<&Addr, Uint128> = Map::new("commission_earned");
+
+#[cw_serde]
+pub enum CommissionSchedule {
+    // share of every bid
+    Flat { rate: Decimal },
+    // same amount for every bid
+    Fixed { amount: Uint128 },
+    // share depending on the bid size, tiers sorted by ascending threshold starting from zero
+    Tiered { tiers: Vec<CommissionTier> },
+}
+
+#[cw_serde]
+pub struct CommissionTier {
+    pub threshold: Uint128,
+    pub rate: Decimal,
+}
+
+#[cw_serde]
+pub struct Commission {
+    pub schedule: CommissionSchedule,
+    pub min: Option<Uint128>,
+    pub max: Option<Uint128>,
+}
+
+impl Commission {
+    pub fn flat(rate: Decimal) -> Commission {
+        Commission {
+            schedule: CommissionSchedule::Flat { rate },
+            min: None,
+            max: None,
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        let schedule_valid = match &self.schedule {
+            CommissionSchedule::Flat { rate } => *rate <= Decimal::one(),
+            CommissionSchedule::Fixed { .. } => true,
+            CommissionSchedule::Tiered { tiers } => {
+                tiers.first().is_some_and(|tier| tier.threshold.is_zero())
+                    && tiers.windows(2).all(|w| w[0].threshold < w[1].threshold)
+                    && tiers.iter().all(|tier| tier.rate <= Decimal::one())
+            }
+        };
+
+        match (self.min, self.max) {
+            (Some(min), Some(max)) => schedule_valid && min <= max,
+            _ => schedule_valid,
+        }
+    }
+
+    // commission on the incoming amount together with the rounding dust it leaves uncharged
+    pub fn calculate(&self, amount: Uint128) -> StdResult<(Uint128, Decimal)> {
+        let (exact, dust) = match &self.schedule {
+            CommissionSchedule::Flat { rate } => Self::apply_rate(amount, *rate)?,
+            CommissionSchedule::Fixed { amount } => (*amount, Decimal::zero()),
+            CommissionSchedule::Tiered { tiers } => {
+                let rate = tiers
+                    .iter()
+                    .rev()
+                    .find(|tier| tier.threshold <= amount)
+                    .map(|tier| tier.rate)
+                    .unwrap_or_default();
+                Self::apply_rate(amount, rate)?
+            }
+        };
+
+        let mut commission = exact;
+        if let Some(min) = self.min {
+            commission = commission.max(min);
+        }
+        if let Some(max) = self.max {
+            commission = commission.min(max);
+        }
+
+        // a clamped commission is not the rounded one, so nothing was left uncharged
+        if commission != exact {
+            return Ok((commission, Decimal::zero()));
+        }
+        Ok((commission, dust))
+    }
+
+    // rounds the commission down, the fraction is only tracked and never charged to anyone
+    fn apply_rate(amount: Uint128, rate: Decimal) -> StdResult<(Uint128, Decimal)> {
+        let exact = amount.full_mul(rate.atomics());
+        let precision = Uint256::from(10u128.pow(Decimal::DECIMAL_PLACES));
+
+        let commission = Uint128::try_from(exact / precision)?;
+        let dust = Decimal::new(Uint128::try_from(exact % precision)?);
+
+        Ok((commission, dust))
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum Asset {
+    Native(String),
+    Cw20(Addr),
+}
+
+impl Asset {
+    pub fn transfer_msg(&self, to: &Addr, amount: Uint128) -> StdResult<CosmosMsg> {
+        let msg = match self {
+            Asset::Native(denom) => BankMsg::Send {
+                to_address: to.to_string(),
+                amount: coins(amount.u128(), denom),
+            }
+            .into(),
+            Asset::Cw20(address) => WasmMsg::Execute {
+                contract_addr: address.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: to.to_string(),
+                    amount,
+                })?,
+                funds: vec![],
+            }
+            .into(),
+        };
+
+        Ok(msg)
+    }
+
+    pub fn send_msg(&self, to: &Addr, amount: Uint128, msg: Binary) -> StdResult<CosmosMsg> {
+        let msg = match self {
+            Asset::Native(denom) => WasmMsg::Execute {
+                contract_addr: to.to_string(),
+                msg,
+                funds: coins(amount.u128(), denom),
+            },
+            Asset::Cw20(address) => WasmMsg::Execute {
+                contract_addr: address.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: to.to_string(),
+                    amount,
+                    msg,
+                })?,
+                funds: vec![],
+            },
+        };
+
+        Ok(msg.into())
+    }
+
+    pub fn query_balance(&self, querier: &QuerierWrapper, address: &Addr) -> StdResult<Uint128> {
+        match self {
+            Asset::Native(denom) => Ok(querier.query_balance(address, denom)?.amount),
+            Asset::Cw20(token) => {
+                let resp: BalanceResponse = querier.query_wasm_smart(
+                    token,
+                    &Cw20QueryMsg::Balance {
+                        address: address.to_string(),
+                    },
+                )?;
+                Ok(resp.balance)
+            }
+        }
+    }
+}
+
+impl From<Asset> for AssetInfo {
+    fn from(asset: Asset) -> AssetInfo {
+        match asset {
+            Asset::Native(denom) => AssetInfo::Native { denom },
+            Asset::Cw20(address) => AssetInfo::Cw20 {
+                address: address.to_string(),
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct Referral {
+    pub earned: Uint128,
+    pub claimed: Uint128,
+}
+pub const REFERRALS: Map<&Addr, Referral> = Map::new("referrals");
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct Bid {
     pub retractable: Uint128,
     pub commission: Uint128,
 }
-pub const BIDS: Map<&Addr, Bid> = Map::new("bids");
+
+impl Bid {
+    pub fn total(&self) -> Uint128 {
+        self.retractable + self.commission
+    }
+}
+
+pub struct BidIndexes<'a> {
+    pub total: MultiIndex<'a, u128, Bid, Addr>,
+}
+
+impl<'a> IndexList<Bid> for BidIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Bid>> + '_> {
+        let v: Vec<&dyn Index<Bid>> = vec![&self.total];
+        Box::new(v.into_iter())
+    }
+}
+
+pub fn bids<'a>() -> IndexedMap<'a, &'a Addr, Bid, BidIndexes<'a>> {
+    let indexes = BidIndexes {
+        total: MultiIndex::new(|_pk, bid: &Bid| bid.total().u128(), "bids", "bids__total"),
+    };
+    IndexedMap::new("bids", indexes)
+}
+
+// every single bid placed, keyed by its sequence number
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BidEvent {
+    pub bidder: Addr,
+    pub height: u64,
+    pub time: Timestamp,
+    pub amount: Uint128,
+    pub commission: Uint128,
+    pub total: Uint128,
+}
+pub const BID_SEQUENCE: Item<u64> = Item::new("bid_sequence");
+
+pub struct BidEventIndexes<'a> {
+    pub bidder: MultiIndex<'a, Addr, BidEvent, u64>,
+}
+
+impl<'a> IndexList<BidEvent> for BidEventIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<BidEvent>> + '_> {
+        let v: Vec<&dyn Index<BidEvent>> = vec![&self.bidder];
+        Box::new(v.into_iter())
+    }
+}
+
+pub fn bid_events<'a>() -> IndexedMap<'a, u64, BidEvent, BidEventIndexes<'a>> {
+    let indexes = BidEventIndexes {
+        bidder: MultiIndex::new(
+            |_pk, event: &BidEvent| event.bidder.clone(),
+            "bid_events",
+            "bid_events__bidder",
+        ),
+    };
+    IndexedMap::new("bid_events", indexes)
+}
+
+// bids stored before 0.3.0 under the same namespace, without the total index
+pub const LEGACY_BIDS: Map<&Addr, Bid> = Map::new("bids");
 
 impl Default for Bid {
     fn default() -> Bid {